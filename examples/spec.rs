@@ -0,0 +1,33 @@
+//! Report spec conformance per section:
+//!
+//!     cargo run --example spec [-- path/to/spec.txt ...]
+//!
+//! Without arguments, the vendored CommonMark and GFM specs are run.
+use pure_markdown::options::Options;
+use pure_markdown::spec;
+use std::{env, fs, process};
+
+fn main() {
+    let paths: Vec<String> = env::args().skip(1).collect();
+    let specs = if paths.is_empty() {
+        vec![
+            ("spec/commonmark.txt".to_string(), include_str!("../spec/commonmark.txt").to_string()),
+            ("spec/gfm.txt".to_string(), include_str!("../spec/gfm.txt").to_string()),
+        ]
+    } else {
+        paths
+            .into_iter()
+            .map(|path| match fs::read_to_string(&path) {
+                Ok(text) => (path, text),
+                Err(error) => {
+                    eprintln!("{}: {}", path, error);
+                    process::exit(1);
+                }
+            })
+            .collect()
+    };
+    for (path, text) in specs {
+        println!("{}\n", path);
+        println!("{}", spec::summary(&spec::run(&text, &Options::default())));
+    }
+}
@@ -0,0 +1,560 @@
+//! Styled terminal output.  Control characters in the text are dropped, so
+//! a document can't send its own escape sequences to the terminal.
+use crate::block::Footnotes;
+use crate::types::{Block, Inline, ListItem};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Options of the terminal renderer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiOptions {
+    /// The number of columns to wrap text at.
+    pub width: usize,
+    /// Use ANSI escape sequences for styles.  Without them, the output is
+    /// plain text with the same layout.
+    pub color: bool,
+}
+
+impl Default for AnsiOptions {
+    fn default() -> Self {
+        AnsiOptions { width: 80, color: true }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Style {
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+    strike: bool,
+}
+
+impl Style {
+    /// The SGR parameters of the style, e.g. `1;4` for bold and underlined.
+    fn parameters(&self) -> String {
+        let flags = [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.reverse, "7"),
+            (self.strike, "9"),
+        ];
+        let parameters: Vec<&str> = flags.iter().filter(|(on, _)| *on).map(|(_, parameter)| *parameter).collect();
+        parameters.join(";")
+    }
+}
+
+/// A piece of inline text in one style.  Code isn't broken at its spaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String, Style, bool),
+    Break,
+}
+
+/// `text` without control characters other than newlines and tabs.
+fn printable(text: &str) -> String {
+    text.chars().filter(|ch| !ch.is_control() || *ch == '\n' || *ch == '\t').collect()
+}
+
+/// Split `line` into lines at most `columns` wide.  A character wider than
+/// `columns` gets a line of its own.
+fn split_columns(line: &str, columns: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    let mut width = 0;
+    for ch in line.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if width > 0 && width + ch_width > columns {
+            lines.push(std::mem::take(&mut current));
+            width = 0;
+        }
+        current.push(ch);
+        width += ch_width;
+    }
+    lines.push(current);
+    lines
+}
+
+enum Token {
+    /// A word and its display width.
+    Word(Vec<(String, Style)>, usize),
+    Space,
+    Break,
+}
+
+fn tokens(pieces: &[Piece]) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut word: Vec<(String, Style)> = vec![];
+    let end_word = |word: &mut Vec<(String, Style)>, tokens: &mut Vec<Token>| {
+        if !word.is_empty() {
+            let width = word.iter().map(|(text, _)| text.width()).sum();
+            tokens.push(Token::Word(std::mem::take(word), width));
+        }
+    };
+    for piece in pieces {
+        match piece {
+            Piece::Break => {
+                end_word(&mut word, &mut tokens);
+                tokens.push(Token::Break);
+            }
+            Piece::Text(text, style, breakable) => {
+                let mut current = String::new();
+                for ch in text.chars() {
+                    if *breakable && ch.is_whitespace() {
+                        if !current.is_empty() {
+                            word.push((std::mem::take(&mut current), *style));
+                        }
+                        end_word(&mut word, &mut tokens);
+                        tokens.push(Token::Space);
+                    } else {
+                        current.push(ch);
+                    }
+                }
+                if !current.is_empty() {
+                    word.push((current, *style));
+                }
+            }
+        }
+    }
+    end_word(&mut word, &mut tokens);
+    tokens
+}
+
+struct Renderer<'a> {
+    options: &'a AnsiOptions,
+    footnotes: Footnotes,
+}
+
+impl Renderer<'_> {
+    fn paint(&self, text: &str, style: Style) -> String {
+        let parameters = style.parameters();
+        if !self.options.color || parameters.is_empty() || text.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", parameters, text)
+        }
+    }
+
+    fn pieces(&self, pieces: &mut Vec<Piece>, inlines: &[Inline], style: Style) {
+        for inline in inlines {
+            self.piece(pieces, inline, style);
+        }
+    }
+
+    fn text(&self, pieces: &mut Vec<Piece>, text: &str, style: Style) {
+        pieces.push(Piece::Text(printable(text), style, true));
+    }
+
+    fn piece(&self, pieces: &mut Vec<Piece>, inline: &Inline, style: Style) {
+        match inline {
+            Inline::HardBreak => pieces.push(Piece::Break),
+            Inline::SoftBreak => self.text(pieces, " ", style),
+            Inline::Text(text) | Inline::Abbreviation(text, _) | Inline::Emoji(_, text) => self.text(pieces, text, style),
+            Inline::Literal(ch) => self.text(pieces, &ch.to_string(), style),
+            Inline::Code(code, _) | Inline::Formula(code) => {
+                pieces.push(Piece::Text(printable(code).replace('\t', " "), Style { dim: true, ..style }, false));
+            }
+            Inline::Emphasis(children) => self.pieces(pieces, children, Style { italic: true, ..style }),
+            Inline::Strong(children) => self.pieces(pieces, children, Style { bold: true, ..style }),
+            Inline::Strike(children) => self.pieces(pieces, children, Style { strike: true, ..style }),
+            Inline::Highlight(children) => self.pieces(pieces, children, Style { reverse: true, ..style }),
+            Inline::Subscript(children) | Inline::Superscript(children) => self.pieces(pieces, children, style),
+            Inline::Link(children, url, ..) | Inline::RefLink(children, url, _) => {
+                let start = pieces.len();
+                self.pieces(pieces, children, Style { underline: true, ..style });
+                let text: String = pieces[start..]
+                    .iter()
+                    .map(|piece| match piece {
+                        Piece::Text(text, ..) => text.as_str(),
+                        Piece::Break => " ",
+                    })
+                    .collect();
+                if text != printable(url) {
+                    self.text(pieces, " (", style);
+                    pieces.push(Piece::Text(printable(url), Style { dim: true, ..style }, false));
+                    self.text(pieces, ")", style);
+                }
+            }
+            Inline::AutoLink(link) => pieces.push(Piece::Text(printable(link), Style { underline: true, ..style }, false)),
+            Inline::Reference(_, text, _) => self.text(pieces, text, Style { underline: true, ..style }),
+            Inline::WikiLink(target, anchor, label) => {
+                let text = match (label, anchor) {
+                    (Some(label), _) => label,
+                    (None, Some(anchor)) if target.is_empty() => anchor,
+                    (None, _) => target,
+                };
+                self.text(pieces, text, Style { underline: true, ..style });
+            }
+            Inline::Image(children, ..) => {
+                self.text(pieces, "[image: ", Style { dim: true, ..style });
+                self.pieces(pieces, children, Style { dim: true, ..style });
+                self.text(pieces, "]", Style { dim: true, ..style });
+            }
+            Inline::FootnoteReference(label) => match self.footnotes.number(label) {
+                Some(number) => self.text(pieces, &format!("[{}]", number), Style { dim: true, ..style }),
+                None => self.text(pieces, &format!("[^{}]", label), style),
+            },
+            Inline::RawHTML(_) => {}
+        }
+    }
+
+    /// Wrap `inlines` in `style` into lines at most `width` columns wide.
+    /// Words wider than the line stay whole.
+    fn wrap(&self, inlines: &[Inline], style: Style, width: usize) -> Vec<String> {
+        let mut pieces = vec![];
+        self.pieces(&mut pieces, inlines, style);
+        let mut lines = vec![];
+        let mut line = String::new();
+        let mut line_width = 0;
+        let mut space = false;
+        for token in tokens(&pieces) {
+            match token {
+                Token::Space => space = line_width > 0,
+                Token::Break => {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                    space = false;
+                }
+                Token::Word(word, word_width) => {
+                    if line_width > 0 && line_width + 1 + word_width > width {
+                        lines.push(std::mem::take(&mut line));
+                        line_width = 0;
+                    } else if space {
+                        line.push(' ');
+                        line_width += 1;
+                    }
+                    for (text, style) in word {
+                        line.push_str(&self.paint(&text, style));
+                    }
+                    line_width += word_width;
+                    space = false;
+                }
+            }
+        }
+        if !line.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// A box of box-drawing characters around `content`, with `label` in the
+    /// top border.  Lines too long for a box `width` columns wide are split,
+    /// and a label too long is left out.
+    fn code_box(&self, content: &str, label: Option<&str>, width: usize) -> Vec<String> {
+        let columns = width.saturating_sub(4).max(1);
+        let content = printable(content.trim_end_matches('\n')).replace('\t', "    ");
+        let lines: Vec<String> = content.split('\n').flat_map(|line| split_columns(line, columns)).collect();
+        let label = label
+            .map(printable)
+            .filter(|label| !label.is_empty() && label.width() + 3 <= columns)
+            .map(|label| format!(" {} ", label));
+        let label_width = label.as_ref().map_or(0, |label| label.width() + 1);
+        let inner = lines.iter().map(|line| line.width()).max().unwrap_or(0).max(label_width) + 2;
+        let border = Style { dim: true, ..Style::default() };
+        let top = match &label {
+            Some(label) => format!("┌─{}{}┐", label, "─".repeat(inner - label.width() - 1)),
+            None => format!("┌{}┐", "─".repeat(inner)),
+        };
+        let mut boxed = vec![self.paint(&top, border)];
+        for line in &lines {
+            let padding = " ".repeat(inner - 2 - line.width());
+            boxed.push(format!("{} {}{} {}", self.paint("│", border), line, padding, self.paint("│", border)));
+        }
+        boxed.push(self.paint(&format!("└{}┘", "─".repeat(inner)), border));
+        boxed
+    }
+
+    fn list(&self, items: &[ListItem], tight: bool, width: usize) -> Vec<String> {
+        let mut lines = vec![];
+        for (index, item) in items.iter().enumerate() {
+            let (marker, blocks) = match item {
+                ListItem::TaskListItem(blocks, true) => ("☑ ".to_string(), blocks),
+                ListItem::TaskListItem(blocks, false) => ("☐ ".to_string(), blocks),
+                ListItem::OrderedListItem(blocks, number) => (format!("{}. ", number), blocks),
+                ListItem::UnorderedListItem(blocks) => ("• ".to_string(), blocks),
+            };
+            if index > 0 && !tight {
+                lines.push(String::new());
+            }
+            let indent = marker.width();
+            let content = self.blocks(blocks, width.saturating_sub(indent).max(1));
+            for (line_index, line) in content.into_iter().enumerate() {
+                match (line_index, line.is_empty()) {
+                    (0, _) => lines.push(format!("{}{}", marker, line)),
+                    (_, true) => lines.push(line),
+                    (_, false) => lines.push(format!("{}{}", " ".repeat(indent), line)),
+                }
+            }
+        }
+        lines
+    }
+
+    /// The lines of `block` wrapped at `width`.
+    fn block(&self, block: &Block, width: usize) -> Vec<String> {
+        match block {
+            Block::Paragraph(inlines) => self.wrap(inlines, Style::default(), width),
+            Block::Header(inlines, level, _) => {
+                let style = Style { bold: true, ..Style::default() };
+                let mut lines = self.wrap(inlines, style, width);
+                let rule = match level {
+                    1 => Some("═"),
+                    2 => Some("─"),
+                    _ => None,
+                };
+                if let Some(rule) = rule {
+                    let mut pieces = vec![];
+                    self.pieces(&mut pieces, inlines, style);
+                    let text_width: usize = tokens(&pieces)
+                        .iter()
+                        .map(|token| match token {
+                            Token::Word(_, width) => *width,
+                            _ => 1,
+                        })
+                        .sum();
+                    let rule_width = text_width.min(width);
+                    lines.push(self.paint(&rule.repeat(rule_width), Style { dim: true, ..Style::default() }));
+                }
+                lines
+            }
+            Block::Blockquote(blocks) => {
+                let bar = self.paint("│", Style { dim: true, ..Style::default() });
+                self.blocks(blocks, width.saturating_sub(2).max(1))
+                    .into_iter()
+                    .map(|line| if line.is_empty() { bar.clone() } else { format!("{} {}", bar, line) })
+                    .collect()
+            }
+            Block::CodeBlock(info, code, _) => {
+                let language = info.as_deref().and_then(|info| info.split_whitespace().next());
+                self.code_box(code, language, width)
+            }
+            Block::Formula(formula) => self.code_box(formula, None, width),
+            Block::List(items, _, tight) => self.list(items, *tight, width),
+            Block::HorizontalRule => vec![self.paint(&"─".repeat(width), Style { dim: true, ..Style::default() })],
+            Block::LinkRefence(..) | Block::FootnoteDefinition(..) | Block::AbbreviationDefinition(..) => vec![],
+        }
+    }
+
+    /// The lines of `blocks`, with a blank line between blocks.
+    fn blocks(&self, blocks: &[Block], width: usize) -> Vec<String> {
+        let mut lines = vec![];
+        for block in blocks {
+            let block_lines = self.block(block, width);
+            if block_lines.is_empty() {
+                continue;
+            }
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.extend(block_lines);
+        }
+        lines
+    }
+
+    /// The footnotes after the text, numbered as their references.
+    fn footnotes(&self, blocks: &[Block]) -> Vec<String> {
+        let mut notes: Vec<(usize, &[Block])> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::FootnoteDefinition(label, content) => self.footnotes.number(label).map(|number| (number, &content[..])),
+                _ => None,
+            })
+            .collect();
+        notes.sort_by_key(|(number, _)| *number);
+        let mut lines = vec![];
+        for (number, content) in notes {
+            let marker = format!("[{}] ", number);
+            let indent = marker.width();
+            let content = self.blocks(content, self.options.width.saturating_sub(indent).max(1));
+            for (index, line) in content.into_iter().enumerate() {
+                if index == 0 {
+                    lines.push(format!("{}{}", self.paint(&marker, Style { dim: true, ..Style::default() }), line));
+                } else if line.is_empty() {
+                    lines.push(line);
+                } else {
+                    lines.push(format!("{}{}", " ".repeat(indent), line));
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// Render `blocks` for a terminal, 80 columns wide with colors.
+pub fn render(blocks: &[Block]) -> String {
+    render_with_options(&AnsiOptions::default(), blocks)
+}
+
+pub fn render_with_options(options: &AnsiOptions, blocks: &[Block]) -> String {
+    let renderer = Renderer { options, footnotes: Footnotes::new(blocks) };
+    let mut lines = renderer.blocks(blocks, options.width.max(1));
+    let footnotes = renderer.footnotes(blocks);
+    if !footnotes.is_empty() {
+        lines.push(String::new());
+        lines.extend(footnotes);
+    }
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::{blocks, blocks_with_options};
+    use crate::options::Options;
+    use crate::types::Block::{Blockquote, List, Paragraph};
+    use crate::types::Inline::Text;
+    use crate::types::ListType;
+
+    fn plain(width: usize) -> AnsiOptions {
+        AnsiOptions { width, color: false }
+    }
+
+    fn paragraph(text: &str) -> Block {
+        Paragraph(vec![Text(text.to_string())])
+    }
+
+    #[test]
+    fn test_styles() {
+        let input = "**bold** *italic* `code` [link](https://example.com)\n";
+        let want = "\x1b[1mbold\x1b[0m \x1b[3mitalic\x1b[0m \x1b[2mcode\x1b[0m \x1b[4mlink\x1b[0m \
+(\x1b[2mhttps://example.com\x1b[0m)\n";
+        assert_eq!(render(&blocks(input)), want);
+        assert_eq!(render_with_options(&plain(80), &blocks(input)), "bold italic code link (https://example.com)\n");
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let input = "The quick brown fox jumps over the lazy dog.\n";
+        let want = "The quick
+brown fox
+jumps over
+the lazy
+dog.
+";
+        assert_eq!(render_with_options(&plain(10), &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_wrapping_display_width() {
+        let input = "日本語の テキスト を 折り返す\n";
+        let want = "日本語の
+テキスト を
+折り返す
+";
+        assert_eq!(render_with_options(&plain(12), &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_wrapping_styled() {
+        let input = "some **bold words** here\n";
+        let want = "some \x1b[1mbold\x1b[0m\n\x1b[1mwords\x1b[0m here\n";
+        let options = AnsiOptions { width: 10, color: true };
+        assert_eq!(render_with_options(&options, &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_hard_break() {
+        let input = "one\\\ntwo\n";
+        assert_eq!(render_with_options(&plain(80), &blocks(input)), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_code_block() {
+        let input = "```rust
+fn main() {}
+```
+";
+        let want = "┌─ rust ───────┐
+│ fn main() {} │
+└──────────────┘
+";
+        assert_eq!(render_with_options(&plain(80), &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_code_box_width() {
+        let input = "```rust
+let long = 1234567890;
+```
+";
+        let want = "┌─ rust ─────┐
+│ let long = │
+│  123456789 │
+│ 0;         │
+└────────────┘
+";
+        assert_eq!(render_with_options(&plain(14), &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_control_characters() {
+        let blocks = vec![
+            paragraph("a\x1b[31mred\x07 b\u{9b}c"),
+            Paragraph(vec![Inline::Code("x\x1by".to_string(), Default::default())]),
+            Block::CodeBlock(Some("sh\x1b".to_string()), "\x1b]0;title\x07ls\n".to_string(), Default::default()),
+        ];
+        let want = "a[31mred bc
+
+xy
+
+┌─ sh ───────┐
+│ ]0;titlels │
+└────────────┘
+";
+        assert_eq!(render_with_options(&plain(80), &blocks), want);
+    }
+
+    #[test]
+    fn test_headings() {
+        let input = "# Title
+
+## Sub
+
+### Deep
+";
+        let want = "Title
+═════
+
+Sub
+───
+
+Deep
+";
+        assert_eq!(render_with_options(&plain(80), &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_quotes_and_lists() {
+        let item = |text: &str| ListItem::UnorderedListItem(vec![paragraph(text)]);
+        let blocks = vec![
+            Blockquote(vec![paragraph("a quoted paragraph")]),
+            List(vec![item("first item wraps"), item("second")], ListType::UnorderedList, true),
+            List(vec![ListItem::TaskListItem(vec![paragraph("done")], true)], ListType::TaskList, true),
+        ];
+        let want = "│ a quoted
+│ paragraph
+
+• first item
+  wraps
+• second
+
+☑ done
+";
+        assert_eq!(render_with_options(&plain(12), &blocks), want);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let options = Options { footnotes: true, ..Options::default() };
+        let input = "Text[^a].
+
+[^a]: The note.
+";
+        let want = "Text[1].\n\n[1] The note.\n";
+        assert_eq!(render_with_options(&plain(80), &blocks_with_options(&options, input)), want);
+    }
+}
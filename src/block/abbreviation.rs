@@ -0,0 +1,175 @@
+use crate::options::Options;
+use crate::types::{Block, Block::AbbreviationDefinition, Inline};
+use nom::{
+    bytes::complete::{tag, take_while, take_while1, take_while_m_n},
+    error::{Error, ErrorKind},
+    sequence::{delimited, preceded},
+    Err, IResult,
+};
+
+/// An abbreviation definition such as `*[HTML]: Hyper Text Markup Language`.
+pub fn abbreviation_definition<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Block> {
+    if !options.abbreviations {
+        return Err(Err::Error(Error::new(input, ErrorKind::Tag)));
+    }
+    let (input, abbreviation) = preceded(
+        take_while_m_n(0, 3, |ch| ch == ' '),
+        delimited(tag("*["), take_while1(|ch| ch != ']' && ch != '\n'), tag("]:")),
+    )(input)?;
+    let (input, title) = take_while(|ch| ch != '\n')(input)?;
+    let input = input.strip_prefix('\n').unwrap_or(input);
+    let abbreviation = abbreviation.trim();
+    if abbreviation.is_empty() {
+        return Err(Err::Error(Error::new(input, ErrorKind::Verify)));
+    }
+    Ok((input, AbbreviationDefinition(abbreviation.to_string(), title.trim().to_string())))
+}
+
+/// The abbreviations defined anywhere in a document.  A later definition of
+/// the same abbreviation replaces an earlier one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Abbreviations {
+    pub definitions: Vec<(String, String)>,
+}
+
+impl Abbreviations {
+    pub fn new(blocks: &[Block]) -> Self {
+        let mut abbreviations = Abbreviations::default();
+        abbreviations.collect(blocks);
+        abbreviations
+            .definitions
+            .sort_by_key(|(abbreviation, _)| std::cmp::Reverse(abbreviation.len()));
+        abbreviations
+    }
+
+    fn collect(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            match block {
+                AbbreviationDefinition(abbreviation, title) => {
+                    self.definitions.retain(|(defined, _)| defined != abbreviation);
+                    self.definitions.push((abbreviation.clone(), title.clone()));
+                }
+                Block::Blockquote(blocks) | Block::FootnoteDefinition(_, blocks) => self.collect(blocks),
+                _ => {}
+            }
+        }
+    }
+
+    fn is_word(ch: char) -> bool {
+        ch.is_alphanumeric() || ch == '_'
+    }
+
+    fn expand_text(&self, text: &str, result: &mut Vec<Inline>) {
+        let mut start = 0;
+        let mut index = 0;
+        let mut prev = None;
+        while let Some(ch) = text[index..].chars().next() {
+            let rest = &text[index..];
+            let found = if prev.is_some_and(Self::is_word) {
+                None
+            } else {
+                self.definitions.iter().find(|(abbreviation, _)| {
+                    rest.starts_with(abbreviation.as_str()) && !rest[abbreviation.len()..].starts_with(Self::is_word)
+                })
+            };
+            if let Some((abbreviation, title)) = found {
+                if start < index {
+                    result.push(Inline::Text(text[start..index].to_string()));
+                }
+                result.push(Inline::Abbreviation(abbreviation.clone(), title.clone()));
+                index += abbreviation.len();
+                start = index;
+                prev = abbreviation.chars().last();
+                continue;
+            }
+            prev = Some(ch);
+            index += ch.len_utf8();
+        }
+        if start < text.len() {
+            result.push(Inline::Text(text[start..].to_string()));
+        }
+    }
+
+    /// Wrap whole-word occurrences of the abbreviations in text into
+    /// `Inline::Abbreviation` nodes, longest abbreviation first.
+    pub fn expand(&self, inlines: Vec<Inline>) -> Vec<Inline> {
+        let mut result = vec![];
+        for inline in inlines {
+            match inline {
+                Inline::Text(text) => self.expand_text(&text, &mut result),
+                Inline::Emphasis(children) => result.push(Inline::Emphasis(self.expand(children))),
+                Inline::Strong(children) => result.push(Inline::Strong(self.expand(children))),
+                Inline::Strike(children) => result.push(Inline::Strike(self.expand(children))),
+                Inline::Subscript(children) => result.push(Inline::Subscript(self.expand(children))),
+                Inline::Superscript(children) => result.push(Inline::Superscript(self.expand(children))),
+                Inline::Highlight(children) => result.push(Inline::Highlight(self.expand(children))),
+                Inline::Link(children, destination, title, attributes) => {
+                    result.push(Inline::Link(self.expand(children), destination, title, attributes))
+                }
+                inline => result.push(inline),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::types::Inline::{Abbreviation, Code, Emphasis, Text};
+    use crate::types::Attributes;
+
+    fn options() -> Options {
+        Options {
+            abbreviations: true,
+            ..Options::default()
+        }
+    }
+
+    fn abbreviation(abbreviation: &str, title: &str) -> Inline {
+        Abbreviation(abbreviation.to_string(), title.to_string())
+    }
+
+    #[test]
+    fn test_abbreviation_definition() {
+        let input = "*[HTML]: Hyper Text Markup Language
+next";
+        let want = AbbreviationDefinition("HTML".to_string(), "Hyper Text Markup Language".to_string());
+        assert_eq!(abbreviation_definition(&options(), input), Ok(("next", want)));
+    }
+
+    #[test]
+    fn test_abbreviation_definition_disabled() {
+        assert!(abbreviation_definition(&Options::default(), "*[HTML]: Hyper Text").is_err());
+        assert!(abbreviation_definition(&options(), "*[]: nothing").is_err());
+    }
+
+    #[test]
+    fn test_expand() {
+        let blocks = vec![
+            AbbreviationDefinition("HTML".to_string(), "old".to_string()),
+            AbbreviationDefinition("W3C".to_string(), "World Wide Web Consortium".to_string()),
+            AbbreviationDefinition("HTML".to_string(), "Hyper Text Markup Language".to_string()),
+            AbbreviationDefinition("HTML 5".to_string(), "Fifth HTML".to_string()),
+        ];
+        let abbreviations = Abbreviations::new(&blocks);
+        let input = vec![
+            Text("The W3C specifies HTML 5, HTML and XHTML, not HTMLs.".to_string()),
+            Emphasis(vec![Text("HTML".to_string())]),
+            Code("HTML".to_string(), Attributes::default()),
+        ];
+        let want = vec![
+            Text("The ".to_string()),
+            abbreviation("W3C", "World Wide Web Consortium"),
+            Text(" specifies ".to_string()),
+            abbreviation("HTML 5", "Fifth HTML"),
+            Text(", ".to_string()),
+            abbreviation("HTML", "Hyper Text Markup Language"),
+            Text(" and XHTML, not HTMLs.".to_string()),
+            Emphasis(vec![abbreviation("HTML", "Hyper Text Markup Language")]),
+            Code("HTML".to_string(), Attributes::default()),
+        ];
+        assert_eq!(abbreviations.expand(input), want);
+    }
+}
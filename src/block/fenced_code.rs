@@ -1,6 +1,7 @@
-#![allow(dead_code)]
+use super::heading::trailing_attributes;
+use crate::options::Options;
 use crate::parser_combinator::take_except;
-use crate::types::{Block, Block::CodeBlock};
+use crate::types::{Attributes, Block, Block::CodeBlock};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while, take_while_m_n},
@@ -11,26 +12,23 @@ use nom::{
     IResult,
 };
 
-fn block_end(
-    input: &str,
-    c: char,
-    cnt: usize,
-) -> IResult<&str, (char, &str, Vec<char>, &str, &str, &str)> {
+type BlockEnd<'a> = (char, &'a str, Vec<char>, &'a str, &'a str, &'a str);
+
+fn block_end(input: &str, c: char, length: usize) -> IResult<&str, BlockEnd<'_>> {
     tuple((
         char('\n'),
         take_while_m_n(0, 3, |ch| ch == ' '),
-        count(char(c), 3 + cnt),
+        count(char(c), length),
         take_while(|ch| ch == c),
         take_while(|ch| ch == ' '),
         alt((eof, tag("\n"))),
     ))(input)
 }
 
-fn block_end_parser(
-    c: char,
-    count: usize,
-) -> impl Fn(&str) -> IResult<&str, (char, &str, Vec<char>, &str, &str, &str)> {
-    move |s: &str| block_end(s, c, count)
+/// A closing fence of at least `length` characters `c` on its own line,
+/// including the line ending before it.
+pub fn block_end_parser(c: char, length: usize) -> impl Fn(&str) -> IResult<&str, BlockEnd<'_>> {
+    move |s: &str| block_end(s, c, length)
 }
 
 fn peek_char(input: &str) -> IResult<&str, char> {
@@ -42,7 +40,7 @@ fn info_string(input: &str) -> IResult<&str, &str> {
     take_while(|c| c != '\n')(input)
 }
 
-fn remove_spaces(count: usize, content: &str) -> String {
+pub fn remove_spaces(count: usize, content: &str) -> String {
     let mut content = content;
     let mut content_string: String = content.to_string();
     for _ in 0..count {
@@ -62,25 +60,38 @@ pub fn fenced_code_block(input: &str) -> IResult<&str, Block> {
     let (input, info) = info_string(input)?;
     let info = info.trim_matches(|ch| ch == c || ch == ' ');
 
-    let (input, content) = take_except(block_end_parser(c, count))(input)?;
-    let (input, _) = opt(block_end_parser(c, count))(input)?;
+    let (input, content) = take_except(block_end_parser(c, 3 + count))(input)?;
+    let (input, _) = opt(block_end_parser(c, 3 + count))(input)?;
 
     let content = remove_spaces(spaces.len(), content);
-    let content = match content.chars().nth(0) {
+    let content = match content.chars().next() {
         Some('\n') => &content[1..],
         _ => &content,
     };
 
     Ok((
         input,
-        CodeBlock(Some(info.to_string()), content.to_string()),
+        CodeBlock(Some(info.to_string()), content.to_string(), Attributes::default()),
     ))
 }
 
+/// A fenced code block.  With `options.attributes`, an attribute list at the
+/// end of the info string applies to the block, as in ```` ```rust {#main} ````.
+pub fn fenced_code_block_with_options<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Block> {
+    let (input, block) = fenced_code_block(input)?;
+    if let CodeBlock(Some(info), content, _) = &block {
+        if let Some((info, attributes)) = trailing_attributes(info).filter(|_| options.attributes) {
+            return Ok((input, CodeBlock(Some(info.to_string()), content.clone(), attributes)));
+        }
+    }
+    Ok((input, block))
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::spec::{run, COMMONMARK};
 
     #[test]
     fn test_commonmark_ex88_5() {
@@ -94,6 +105,7 @@ mod tests {
             "<
  >"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -111,6 +123,7 @@ mod tests {
             "<
  >"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -128,6 +141,7 @@ mod tests {
             "<
  >"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -145,6 +159,7 @@ aaa
             "aaa
 ~~~"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -161,6 +176,7 @@ aaa
             "aaa
 ```"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -179,6 +195,7 @@ aaa
             "aaa
 ```"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -196,6 +213,7 @@ aaa
             "aaa
 ```"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -212,6 +230,7 @@ aaa
             "aaa
 ~~~"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -230,6 +249,7 @@ aaa
 ~~~
 "
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -248,6 +268,7 @@ aaa";
 ```
 aaa"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -265,6 +286,7 @@ aaa"
             "
   "
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -275,7 +297,7 @@ aaa"
     fn test_commonmark_ex100() {
         let input = "```
 ```";
-        let want = CodeBlock(Some("".to_string()), "".to_string());
+        let want = CodeBlock(Some("".to_string()), "".to_string(), Attributes::default());
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
@@ -291,6 +313,7 @@ aaa
             "aaa
 aaa"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -310,6 +333,7 @@ aaa
 aaa
 aaa"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -328,6 +352,7 @@ aaa"
  aaa
 aaa"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -338,7 +363,7 @@ aaa"
         let input = "```
 aaa
   ```";
-        let want = CodeBlock(Some("".to_string()), "aaa".to_string());
+        let want = CodeBlock(Some("".to_string()), "aaa".to_string(), Attributes::default());
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
@@ -348,7 +373,7 @@ aaa
         let input = "   ```
 aaa
   ```";
-        let want = CodeBlock(Some("".to_string()), "aaa".to_string());
+        let want = CodeBlock(Some("".to_string()), "aaa".to_string(), Attributes::default());
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
@@ -363,6 +388,7 @@ aaa
             "aaa
     ```"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -378,6 +404,7 @@ aaa
             "aaa
 ~~~ ~~"
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -396,6 +423,7 @@ end
   return 3
 end"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -415,6 +443,7 @@ end
   return 3
 end"
             .to_string(),
+            Attributes::default(),
         );
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -425,7 +454,7 @@ end"
     fn test_commonmark_ex114() {
         let input = "````;
 ````";
-        let want = CodeBlock(Some(";".to_string()), "".to_string());
+        let want = CodeBlock(Some(";".to_string()), "".to_string(), Attributes::default());
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
@@ -435,7 +464,7 @@ end"
     fn test_commonmark_ex115() {
         let input = "``` aa ```
 foo";
-        let want = CodeBlock(Some("aa".to_string()), "foo".to_string());
+        let want = CodeBlock(Some("aa".to_string()), "foo".to_string(), Attributes::default());
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
@@ -445,7 +474,7 @@ foo";
     fn test_commonmark_ex116() {
         let input = "~~~ aa ~~~
 foo";
-        let want = CodeBlock(Some("aa".to_string()), "foo".to_string());
+        let want = CodeBlock(Some("aa".to_string()), "foo".to_string(), Attributes::default());
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
@@ -456,9 +485,34 @@ foo";
         let input = "```
 ``` aaa
 ```";
-        let want = CodeBlock(Some("".to_string()), "``` aaa".to_string());
+        let want = CodeBlock(Some("".to_string()), "``` aaa".to_string(), Attributes::default());
         let (input, result) = fenced_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
     }
+
+    #[test]
+    fn test_attributes() {
+        let input = "```rust {#main .numberLines}\nfn main() {}\n```";
+        let attributes = Attributes {
+            id: Some("main".to_string()),
+            classes: vec!["numberLines".to_string()],
+            pairs: vec![],
+        };
+        let want = CodeBlock(Some("rust".to_string()), "fn main() {}".to_string(), attributes);
+        let options = Options { attributes: true, ..Options::default() };
+        assert_eq!(fenced_code_block_with_options(&options, input), Ok(("", want)));
+        let info = Some("rust {#main .numberLines}".to_string());
+        let want = CodeBlock(info, "fn main() {}".to_string(), Attributes::default());
+        assert_eq!(fenced_code_block_with_options(&Options::default(), input), Ok(("", want)));
+    }
+
+    /// Every example of the section, parsed and rendered.  Remove examples
+    /// from the known failures as they are fixed.
+    #[test]
+    fn test_spec_examples() {
+        let reports = run(COMMONMARK, &Options::default());
+        let report = reports.iter().find(|report| report.section == "Fenced code blocks").unwrap();
+        assert_eq!(report.failed, vec![128, 138, 141, 145]);
+    }
 }
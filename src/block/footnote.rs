@@ -0,0 +1,206 @@
+use super::blocks_with_options;
+use crate::inline::footnote_label;
+use crate::options::Options;
+use crate::types::{Block, Block::FootnoteDefinition, Inline, ListItem};
+use nom::{
+    bytes::complete::take_while_m_n,
+    character::complete::char,
+    error::{Error, ErrorKind},
+    sequence::{terminated, tuple},
+    Err, IResult,
+};
+
+fn split_line(input: &str) -> (&str, &str) {
+    match input.find('\n') {
+        Some(end) => (&input[..end], &input[end + 1..]),
+        None => (input, ""),
+    }
+}
+
+/// `[^label]:` at the start of a line, indented by up to three spaces.
+pub fn footnote_start(input: &str) -> IResult<&str, String> {
+    let (input, (_, label)) = tuple((
+        take_while_m_n(0, 3, |ch| ch == ' '),
+        terminated(footnote_label, char(':')),
+    ))(input)?;
+    Ok((input, label))
+}
+
+/// A footnote definition: `[^label]:` followed by the first line of content,
+/// then continuation lines indented by four spaces, blank lines between them,
+/// and lazy paragraph continuation lines.  The content is parsed as blocks.
+pub fn footnote_definition<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Block> {
+    if !options.footnotes {
+        return Err(Err::Error(Error::new(input, ErrorKind::Tag)));
+    }
+    let (input, label) = footnote_start(input)?;
+    let (first, mut rest) = split_line(input);
+    let first = first.trim_start();
+    let mut lines = vec![first];
+    let mut lazy = !first.is_empty();
+    while !rest.is_empty() {
+        let (line, remaining) = split_line(rest);
+        if let Some(content) = line.strip_prefix("    ") {
+            lines.push(content);
+            lazy = !content.trim().is_empty();
+        } else if line.trim().is_empty() {
+            let next = remaining.lines().find(|line| !line.trim().is_empty());
+            if !next.is_some_and(|line| line.starts_with("    ")) {
+                break;
+            }
+            lines.push("");
+            lazy = false;
+        } else if lazy && !super::interrupts_paragraph(options, rest) {
+            lines.push(line);
+        } else {
+            break;
+        }
+        rest = remaining;
+    }
+    let content = lines.join("\n");
+    Ok((rest, FootnoteDefinition(label, blocks_with_options(options, &content))))
+}
+
+/// Footnote numbering for a document.  Footnotes are numbered in the order of
+/// their first reference.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Footnotes {
+    /// Labels of the footnotes that are both referenced and defined, in
+    /// numbering order.
+    pub order: Vec<String>,
+    /// Labels that are referenced but never defined.
+    pub undefined: Vec<String>,
+    /// Labels that are defined but never referenced.
+    pub unused: Vec<String>,
+}
+
+fn visit_inlines(inlines: &[Inline], references: &mut Vec<String>) {
+    for inline in inlines {
+        match inline {
+            Inline::FootnoteReference(label) if !references.contains(label) => references.push(label.clone()),
+            Inline::Link(children, ..)
+            | Inline::RefLink(children, ..)
+            | Inline::Image(children, ..)
+            | Inline::Emphasis(children)
+            | Inline::Strong(children)
+            | Inline::Strike(children)
+            | Inline::Highlight(children)
+            | Inline::Subscript(children)
+            | Inline::Superscript(children) => visit_inlines(children, references),
+            _ => {}
+        }
+    }
+}
+
+fn visit_blocks(blocks: &[Block], references: &mut Vec<String>, definitions: &mut Vec<String>) {
+    for block in blocks {
+        match block {
+            Block::Header(inlines, ..) | Block::Paragraph(inlines) => visit_inlines(inlines, references),
+            Block::Blockquote(blocks) => visit_blocks(blocks, references, definitions),
+            Block::List(items, ..) => {
+                for item in items {
+                    match item {
+                        ListItem::TaskListItem(blocks, _)
+                        | ListItem::OrderedListItem(blocks, _)
+                        | ListItem::UnorderedListItem(blocks) => visit_blocks(blocks, references, definitions),
+                    }
+                }
+            }
+            Block::FootnoteDefinition(label, blocks) => {
+                if !definitions.contains(label) {
+                    definitions.push(label.clone());
+                }
+                visit_blocks(blocks, references, definitions);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Footnotes {
+    pub fn new(blocks: &[Block]) -> Self {
+        let mut references = vec![];
+        let mut definitions = vec![];
+        visit_blocks(blocks, &mut references, &mut definitions);
+        let (order, undefined): (Vec<String>, Vec<String>) = references.into_iter().partition(|label| definitions.contains(label));
+        let unused = definitions.into_iter().filter(|label| !order.contains(label)).collect();
+        Footnotes { order, undefined, unused }
+    }
+
+    /// The number of the footnote with the given normalized label, counting
+    /// from 1.
+    pub fn number(&self, label: &str) -> Option<usize> {
+        self.order.iter().position(|defined| defined == label).map(|index| index + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::types::Attributes;
+    use crate::types::Block::{CodeBlock, Paragraph};
+    use crate::types::Inline::{FootnoteReference, SoftBreak, Text};
+
+    fn options() -> Options {
+        Options {
+            footnotes: true,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn test_footnote_definition() {
+        let input = "[^Note]: Here is the note.
+continued lazily
+
+    ```rust
+    fn main() {}
+    ```
+
+    Second paragraph.
+
+Not part of the note.";
+        let want = FootnoteDefinition(
+            "note".to_string(),
+            vec![
+                Paragraph(vec![
+                    Text("Here is the note.".to_string()),
+                    SoftBreak,
+                    Text("continued lazily".to_string()),
+                ]),
+                CodeBlock(Some("rust".to_string()), "fn main() {}".to_string(), Attributes::default()),
+                Paragraph(vec![Text("Second paragraph.".to_string())]),
+            ],
+        );
+        let (input, result) = footnote_definition(&options(), input).unwrap();
+        assert_eq!(input, "\nNot part of the note.");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_footnote_definition_disabled() {
+        assert!(footnote_definition(&Options::default(), "[^1]: note").is_err());
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let blocks = vec![
+            Paragraph(vec![
+                FootnoteReference("b".to_string()),
+                FootnoteReference("missing".to_string()),
+                FootnoteReference("a".to_string()),
+                FootnoteReference("b".to_string()),
+            ]),
+            FootnoteDefinition("a".to_string(), vec![]),
+            FootnoteDefinition("b".to_string(), vec![]),
+            FootnoteDefinition("unused".to_string(), vec![]),
+        ];
+        let footnotes = Footnotes::new(&blocks);
+        assert_eq!(footnotes.order, vec!["b".to_string(), "a".to_string()]);
+        assert_eq!(footnotes.undefined, vec!["missing".to_string()]);
+        assert_eq!(footnotes.unused, vec!["unused".to_string()]);
+        assert_eq!(footnotes.number("a"), Some(2));
+        assert_eq!(footnotes.number("missing"), None);
+    }
+}
@@ -0,0 +1,185 @@
+use super::fenced_code::{block_end_parser, fenced_code_block, remove_spaces};
+use crate::options::Options;
+use crate::parser_combinator::take_except;
+use crate::types::{Block, Block::CodeBlock, Block::Formula};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while, take_while_m_n},
+    character::complete::char,
+    combinator::opt,
+    error::{Error, ErrorKind},
+    sequence::tuple,
+    Err, IResult,
+};
+
+/// `$$ ... $$` on a single line, or an opening `$$` line followed by the
+/// formula and a closing `$$` line.  Unlike a code fence, an unclosed `$$` is
+/// not a formula.
+fn dollar_block(input: &str) -> IResult<&str, Block> {
+    let (input, (spaces, _)) = tuple((take_while_m_n(0, 3, |ch| ch == ' '), tag("$$")))(input)?;
+    let (input, first_line) = take_while(|ch| ch != '\n')(input)?;
+    let first_line = first_line.trim();
+
+    if let Some(formula) = first_line.strip_suffix("$$") {
+        if !formula.trim().is_empty() {
+            let (input, _) = opt(char('\n'))(input)?;
+            return Ok((input, Formula(formula.trim().to_string())));
+        }
+    }
+
+    let (input, content) = take_except(block_end_parser('$', 2))(input)?;
+    let (input, _) = block_end_parser('$', 2)(input)?;
+
+    let content = remove_spaces(spaces.len(), content);
+    let content = match content.strip_prefix('\n') {
+        Some(content) => content,
+        None => &content,
+    };
+    let content = if first_line.is_empty() {
+        content.to_string()
+    } else if content.is_empty() {
+        first_line.to_string()
+    } else {
+        format!("{}\n{}", first_line, content)
+    };
+    Ok((input, Formula(content)))
+}
+
+/// A fenced code block whose info string is `math`.
+fn math_code_block(input: &str) -> IResult<&str, Block> {
+    match fenced_code_block(input)? {
+        (input, CodeBlock(Some(info), content, _)) if info.split_whitespace().next() == Some("math") => {
+            Ok((input, Formula(content)))
+        }
+        _ => Err(Err::Error(Error::new(input, ErrorKind::Verify))),
+    }
+}
+
+pub fn formula_block<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Block> {
+    if !options.math {
+        return Err(Err::Error(Error::new(input, ErrorKind::Tag)));
+    }
+    alt((dollar_block, math_code_block))(input)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn options() -> Options {
+        Options { math: true, ..Options::default() }
+    }
+
+    #[test]
+    fn test_formula_block() {
+        let input = "$$
+\\sum_{i=1}^n i
+  = \\frac{n(n+1)}{2}
+$$
+";
+        let want = Formula(
+            "\\sum_{i=1}^n i
+  = \\frac{n(n+1)}{2}"
+                .to_string(),
+        );
+        let (input, result) = formula_block(&options(), input).unwrap();
+        assert_eq!(input, "");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_formula_block_single_line() {
+        let input = "$$ e^{i\\pi} + 1 = 0 $$
+next";
+        let want = Formula("e^{i\\pi} + 1 = 0".to_string());
+        let (input, result) = formula_block(&options(), input).unwrap();
+        assert_eq!(input, "next");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_formula_block_content_on_opening_line() {
+        let input = "$$x = 1
+y = 2
+$$";
+        let want = Formula(
+            "x = 1
+y = 2"
+                .to_string(),
+        );
+        let (input, result) = formula_block(&options(), input).unwrap();
+        assert_eq!(input, "");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_formula_block_indented() {
+        let input = "  $$
+  a
+   b
+  $$";
+        let want = Formula(
+            "a
+ b"
+            .to_string(),
+        );
+        let (input, result) = formula_block(&options(), input).unwrap();
+        assert_eq!(input, "");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_formula_block_longer_closing() {
+        let input = "$$
+a
+$$$
+b";
+        let want = Formula("a".to_string());
+        let (input, result) = formula_block(&options(), input).unwrap();
+        assert_eq!(input, "b");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_formula_block_unclosed() {
+        let input = "$$
+a + b";
+        assert!(formula_block(&options(), input).is_err());
+    }
+
+    #[test]
+    fn test_formula_block_fenced_math() {
+        let input = "```math
+a^2 + b^2 = c^2
+```";
+        let want = Formula("a^2 + b^2 = c^2".to_string());
+        let (input, result) = formula_block(&options(), input).unwrap();
+        assert_eq!(input, "");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_formula_block_non_ascii() {
+        let (input, result) = formula_block(&options(), "$$\nα + β\n$$\n").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(result, Formula("α + β".to_string()));
+        let (input, result) = formula_block(&options(), "```math\nα ≤ β\n```").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(result, Formula("α ≤ β".to_string()));
+    }
+
+    #[test]
+    fn test_formula_block_fenced_other() {
+        let input = "```rust
+fn main() {}
+```";
+        assert!(formula_block(&options(), input).is_err());
+    }
+
+    #[test]
+    fn test_formula_block_disabled_by_default() {
+        assert!(formula_block(&Options::default(), "$$\nx\n$$").is_err());
+        assert!(formula_block(&Options::default(), "```math\nx\n```").is_err());
+    }
+}
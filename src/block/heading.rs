@@ -0,0 +1,184 @@
+use crate::inline::{attributes, inlines_with_options};
+use crate::options::Options;
+use crate::types::{Attributes, Block, Block::Header};
+use nom::{
+    bytes::complete::take_while_m_n,
+    error::{Error, ErrorKind},
+    sequence::tuple,
+    Err, IResult,
+};
+
+/// Split an attribute list off the end of a heading or an info string.  It has
+/// to be separated from the text by whitespace, as in `## Install {#install}`.
+pub(super) fn trailing_attributes(content: &str) -> Option<(&str, Attributes)> {
+    let start = content.rfind('{')?;
+    let before = &content[..start];
+    if !(before.is_empty() || before.ends_with(' ') || before.ends_with('\t')) {
+        return None;
+    }
+    match attributes(&content[start..]) {
+        Ok(("", attributes)) => Some((before.trim_end(), attributes)),
+        _ => None,
+    }
+}
+
+/// `content` without its closing sequence of `#`, which must be preceded by
+/// whitespace unless it is all there is.
+fn strip_closing(content: &str) -> &str {
+    let without_closing = content.trim_end_matches('#');
+    if without_closing.is_empty() {
+        ""
+    } else if without_closing.ends_with(' ') || without_closing.ends_with('\t') {
+        without_closing.trim_end()
+    } else {
+        content
+    }
+}
+
+/// An ATX heading: up to three spaces of indentation, one to six `#`, and the
+/// content up to an optional closing sequence of `#`.  With
+/// `options.attributes`, an attribute list at the end of the line applies to
+/// the heading, before or after the closing sequence.
+pub fn atx_heading<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Block> {
+    let (rest, (_, hashes)) = tuple((
+        take_while_m_n(0, 3, |ch| ch == ' '),
+        take_while_m_n(1, 6, |ch| ch == '#'),
+    ))(input)?;
+    let (line, rest) = match rest.find('\n') {
+        Some(end) => (&rest[..end], &rest[end + 1..]),
+        None => (rest, ""),
+    };
+    if !(line.is_empty() || line.starts_with(' ') || line.starts_with('\t')) {
+        return Err(Err::Error(Error::new(input, ErrorKind::Space)));
+    }
+
+    let mut content = strip_closing(line.trim());
+    let mut attributes = Attributes::default();
+    if options.attributes {
+        if let Some((before, trailing)) = trailing_attributes(content) {
+            content = strip_closing(before);
+            attributes = trailing;
+        }
+    }
+    Ok((rest, Header(inlines_with_options(options, content), hashes.len(), attributes)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::types::Inline::{Emphasis, Literal, Text};
+
+    fn heading(input: &str) -> IResult<&str, Block> {
+        atx_heading(&Options::default(), input)
+    }
+
+    fn header(content: &str, level: usize) -> Block {
+        Header(vec![Text(content.to_string())], level, Attributes::default())
+    }
+
+    #[test]
+    fn test_commonmark_ex32() {
+        let mut input = "# foo
+## foo
+### foo
+#### foo
+##### foo
+###### foo";
+        for level in 1..=6 {
+            let (rest, result) = heading(input).unwrap();
+            assert_eq!(header("foo", level), result);
+            input = rest;
+        }
+        assert_eq!(input, "");
+    }
+
+    #[test]
+    fn test_commonmark_ex33() {
+        assert!(heading("####### foo").is_err());
+    }
+
+    #[test]
+    fn test_commonmark_ex34() {
+        assert!(heading("#5 bolt").is_err());
+        assert!(heading("#hashtag").is_err());
+    }
+
+    #[test]
+    fn test_commonmark_ex36() {
+        let input = "# foo *bar* \\*baz\\*";
+        let want = Header(
+            vec![
+                Text("foo ".to_string()),
+                Emphasis(vec![Text("bar".to_string())]),
+                Text(" ".to_string()),
+                Literal('*'),
+                Text("baz".to_string()),
+                Literal('*'),
+            ],
+            1,
+            Attributes::default(),
+        );
+        assert_eq!(heading(input), Ok(("", want)));
+    }
+
+    #[test]
+    fn test_commonmark_ex37() {
+        let input = "#                  foo                     ";
+        assert_eq!(heading(input), Ok(("", header("foo", 1))));
+    }
+
+    #[test]
+    fn test_commonmark_ex39() {
+        assert!(heading("    # foo").is_err());
+    }
+
+    #[test]
+    fn test_commonmark_ex41() {
+        let input = "## foo ##
+  ###   bar    ###";
+        let (input, result) = heading(input).unwrap();
+        assert_eq!(header("foo", 2), result);
+        assert_eq!(heading(input), Ok(("", header("bar", 3))));
+    }
+
+    #[test]
+    fn test_commonmark_ex44() {
+        assert_eq!(heading("### foo ### b"), Ok(("", header("foo ### b", 3))));
+    }
+
+    #[test]
+    fn test_commonmark_ex45() {
+        assert_eq!(heading("# foo#"), Ok(("", header("foo#", 1))));
+    }
+
+    #[test]
+    fn test_commonmark_ex49() {
+        let empty = |level| Header(vec![], level, Attributes::default());
+        assert_eq!(heading("## "), Ok(("", empty(2))));
+        assert_eq!(heading("#"), Ok(("", empty(1))));
+        assert_eq!(heading("### ###"), Ok(("", empty(3))));
+    }
+
+    #[test]
+    fn test_heading_attributes() {
+        let options = Options {
+            attributes: true,
+            ..Options::default()
+        };
+        let want = Header(
+            vec![Text("Install".to_string())],
+            2,
+            Attributes {
+                id: Some("install".to_string()),
+                classes: vec!["tip".to_string()],
+                pairs: vec![],
+            },
+        );
+        assert_eq!(atx_heading(&options, "## Install {#install .tip}"), Ok(("", want.clone())));
+        assert_eq!(atx_heading(&options, "## Install ## {#install .tip}"), Ok(("", want.clone())));
+        assert_eq!(atx_heading(&options, "## Install {#install .tip} ##"), Ok(("", want)));
+        assert_eq!(atx_heading(&options, "## Install{#install}"), Ok(("", header("Install{#install}", 2))));
+        assert_eq!(heading("## Install {#install}"), Ok(("", header("Install {#install}", 2))));
+    }
+}
@@ -1,5 +1,4 @@
-#![allow(dead_code)]
-use crate::types::{Block, Block::CodeBlock};
+use crate::types::{Attributes, Block, Block::CodeBlock};
 
 use nom::{IResult, bytes::complete::{tag, take_till, take_while, take_while_m_n}, character::complete::char, combinator::opt, multi::{fold_many1, many0_count, many1}, sequence::tuple};
 
@@ -14,8 +13,9 @@ pub fn indented_chunk(input: &str) -> IResult<&str, String> {
     Ok((input, content.to_string()))
 }
 
+/// Lines made of spaces and tabs only.
 pub fn blanklines(input: &str) -> IResult<&str, usize> {
-    let (input, count) = many0_count(tuple((take_while(|ch| ch == ' '), char('\n'))))(input)?;
+    let (input, count) = many0_count(tuple((take_while(|ch| ch == ' ' || ch == '\t'), char('\n'))))(input)?;
     Ok((input, count))
 }
 
@@ -38,13 +38,15 @@ pub fn indented_code_block(input: &str) -> IResult<&str, Block> {
         }
     });
     let content = lines.concat();
-    Ok((input, CodeBlock(None, content)))
+    Ok((input, CodeBlock(None, content, Attributes::default())))
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::options::Options;
+    use crate::spec::{run, COMMONMARK};
 
     #[test]
     fn test_commonmark_ex77() {
@@ -55,6 +57,7 @@ mod tests {
             "a simple
   indented code block"
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = indented_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -74,6 +77,7 @@ mod tests {
 
 - one"
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = indented_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -100,6 +104,7 @@ chunk2
 chunk3
 "
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = indented_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -117,6 +122,7 @@ chunk3
   
   chunk2"
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = indented_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -131,6 +137,7 @@ bar";
             None,
             "foo\n"
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = indented_code_block(input).unwrap();
         assert_eq!(input, "bar");
@@ -145,6 +152,7 @@ bar";
             "    foo
 bar"
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = indented_code_block(input).unwrap();
         assert_eq!(input, "");
@@ -158,9 +166,19 @@ bar"
             None,
             "foo  "
                 .to_string(),
+            Attributes::default(),
         );
         let (input, result) = indented_code_block(input).unwrap();
         assert_eq!(input, "");
         assert_eq!(want, result);
     }
+
+    /// Every example of the section, parsed and rendered.  Remove examples
+    /// from the known failures as they are fixed.
+    #[test]
+    fn test_spec_examples() {
+        let reports = run(COMMONMARK, &Options::default());
+        let report = reports.iter().find(|report| report.section == "Indented code blocks").unwrap();
+        assert_eq!(report.failed, vec![108, 109, 115, 117]);
+    }
 }
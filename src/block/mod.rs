@@ -1,2 +1,238 @@
+use crate::inline::inlines_with_options;
+use crate::options::Options;
+use crate::types::{Block, Inline, ListItem};
+use nom::{branch::alt, IResult};
+
+mod abbreviation;
 mod fenced_code;
+mod footnote;
+mod formula;
+mod heading;
 mod indented_code;
+mod paragraph;
+
+pub use abbreviation::Abbreviations;
+pub use footnote::Footnotes;
+
+/// Whether the line at the start of `input` opens a block that can interrupt
+/// a paragraph.
+fn interrupts_paragraph(options: &Options, input: &str) -> bool {
+    heading::atx_heading(options, input).is_ok()
+        || formula::formula_block(options, input).is_ok()
+        || fenced_code::fenced_code_block_with_options(options, input).is_ok()
+        || (options.footnotes && footnote::footnote_start(input).is_ok())
+        || abbreviation::abbreviation_definition(options, input).is_ok()
+}
+
+fn block<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Block> {
+    alt((
+        |input| footnote::footnote_definition(options, input),
+        |input| abbreviation::abbreviation_definition(options, input),
+        |input| heading::atx_heading(options, input),
+        |input| formula::formula_block(options, input),
+        |input| fenced_code::fenced_code_block_with_options(options, input),
+        indented_code::indented_code_block,
+        |input| paragraph::paragraph(options, input),
+    ))(input)
+}
+
+/// Parse `input` as a sequence of blocks.  With `options.abbreviations`, the
+/// abbreviations defined anywhere in the document are expanded in its text.
+pub fn blocks(input: &str) -> Vec<Block> {
+    blocks_with_options(&Options::default(), input)
+}
+
+pub fn blocks_with_options(options: &Options, input: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut input = input;
+    loop {
+        if let Ok((rest, _)) = indented_code::blanklines(input) {
+            input = rest;
+        }
+        if input.trim().is_empty() {
+            break;
+        }
+        match block(options, input) {
+            Ok((rest, block)) => {
+                blocks.push(block);
+                input = rest;
+            }
+            Err(_) => {
+                // Never drop the rest of the document: take the line as a
+                // paragraph of its own.
+                let (line, rest) = match input.find('\n') {
+                    Some(end) => (&input[..end], &input[end + 1..]),
+                    None => (input, ""),
+                };
+                if !line.trim().is_empty() {
+                    blocks.push(Block::Paragraph(inlines_with_options(options, line.trim())));
+                }
+                input = rest;
+            }
+        }
+    }
+    if options.abbreviations {
+        let abbreviations = Abbreviations::new(&blocks);
+        return map_inlines(blocks, &|inlines| abbreviations.expand(inlines));
+    }
+    blocks
+}
+
+fn map_items(items: Vec<ListItem>, f: &impl Fn(Vec<Inline>) -> Vec<Inline>) -> Vec<ListItem> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            ListItem::TaskListItem(blocks, checked) => ListItem::TaskListItem(map_inlines(blocks, f), checked),
+            ListItem::OrderedListItem(blocks, number) => ListItem::OrderedListItem(map_inlines(blocks, f), number),
+            ListItem::UnorderedListItem(blocks) => ListItem::UnorderedListItem(map_inlines(blocks, f)),
+        })
+        .collect()
+}
+
+/// Apply `f` to the inline content of every block, e.g. to run
+/// `inline::link_references` over a whole document.
+pub fn map_inlines(blocks: Vec<Block>, f: &impl Fn(Vec<Inline>) -> Vec<Inline>) -> Vec<Block> {
+    blocks
+        .into_iter()
+        .map(|block| match block {
+            Block::Header(inlines, level, attributes) => Block::Header(f(inlines), level, attributes),
+            Block::Paragraph(inlines) => Block::Paragraph(f(inlines)),
+            Block::Blockquote(blocks) => Block::Blockquote(map_inlines(blocks, f)),
+            Block::List(items, list_type, tight) => Block::List(map_items(items, f), list_type, tight),
+            Block::FootnoteDefinition(label, blocks) => Block::FootnoteDefinition(label, map_inlines(blocks, f)),
+            block => block,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::types::Attributes;
+    use crate::types::Block::{AbbreviationDefinition, CodeBlock, FootnoteDefinition, Formula, Header, Paragraph};
+    use crate::inline::{issue, link_references, Pattern};
+    use crate::types::Inline::{Abbreviation, FootnoteReference, Reference, Text};
+
+    #[test]
+    fn test_blocks_whitespace_lines() {
+        let text = |content: &str| Paragraph(vec![Text(content.to_string())]);
+        assert_eq!(blocks("a\n\t\nb"), vec![text("a"), text("b")]);
+        assert_eq!(blocks("x\n\n\t\n\ny"), vec![text("x"), text("y")]);
+        assert_eq!(blocks("x\n \t \ny\n"), vec![text("x"), text("y")]);
+        assert_eq!(blocks("x\n\u{3000}\ny"), vec![text("x"), text("y")]);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "para one
+
+```
+code
+```
+$$ x $$
+
+    indented
+";
+        let want = vec![
+            Paragraph(vec![Text("para one".to_string())]),
+            CodeBlock(Some("".to_string()), "code".to_string(), Attributes::default()),
+            Formula("x".to_string()),
+            CodeBlock(None, "indented\n".to_string(), Attributes::default()),
+        ];
+        let options = Options { math: true, ..Options::default() };
+        assert_eq!(blocks_with_options(&options, input), want);
+    }
+
+    #[test]
+    fn test_blocks_non_ascii() {
+        let want = vec![
+            CodeBlock(Some("".to_string()), "é\n日本".to_string(), Attributes::default()),
+            Paragraph(vec![Text("ü".to_string())]),
+        ];
+        assert_eq!(blocks("```\né\n日本\n```\nü"), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex48() {
+        let input = "Foo bar
+# baz
+Bar foo";
+        let want = vec![
+            Paragraph(vec![Text("Foo bar".to_string())]),
+            Header(vec![Text("baz".to_string())], 1, Attributes::default()),
+            Paragraph(vec![Text("Bar foo".to_string())]),
+        ];
+        assert_eq!(blocks(input), want);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let options = Options {
+            footnotes: true,
+            ..Options::default()
+        };
+        let input = "Text[^1].
+[^1]: The note.
+[^2]: Another.";
+        let want = vec![
+            Paragraph(vec![Text("Text".to_string()), FootnoteReference("1".to_string()), Text(".".to_string())]),
+            FootnoteDefinition("1".to_string(), vec![Paragraph(vec![Text("The note.".to_string())])]),
+            FootnoteDefinition("2".to_string(), vec![Paragraph(vec![Text("Another.".to_string())])]),
+        ];
+        let blocks = blocks_with_options(&options, input);
+        assert_eq!(blocks, want);
+        let footnotes = Footnotes::new(&blocks);
+        assert_eq!(footnotes.order, vec!["1".to_string()]);
+        assert_eq!(footnotes.unused, vec!["2".to_string()]);
+    }
+
+    #[test]
+    fn test_map_inlines() {
+        let options = Options {
+            footnotes: true,
+            ..Options::default()
+        };
+        let patterns = vec![Pattern::new("issue", issue, |issue| Some(format!("/issues/{}", &issue[1..])))];
+        let input = "Fixes #1[^a].
+
+[^a]: See #2.";
+        let want = vec![
+            Paragraph(vec![
+                Text("Fixes ".to_string()),
+                Reference("issue".to_string(), "#1".to_string(), "/issues/1".to_string()),
+                FootnoteReference("a".to_string()),
+                Text(".".to_string()),
+            ]),
+            FootnoteDefinition(
+                "a".to_string(),
+                vec![Paragraph(vec![
+                    Text("See ".to_string()),
+                    Reference("issue".to_string(), "#2".to_string(), "/issues/2".to_string()),
+                    Text(".".to_string()),
+                ])],
+            ),
+        ];
+        let blocks = blocks_with_options(&options, input);
+        assert_eq!(map_inlines(blocks, &|inlines| link_references(inlines, &patterns)), want);
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        let options = Options {
+            abbreviations: true,
+            ..Options::default()
+        };
+        let input = "The HTML specification
+*[HTML]: Hyper Text Markup Language";
+        let want = vec![
+            Paragraph(vec![
+                Text("The ".to_string()),
+                Abbreviation("HTML".to_string(), "Hyper Text Markup Language".to_string()),
+                Text(" specification".to_string()),
+            ]),
+            AbbreviationDefinition("HTML".to_string(), "Hyper Text Markup Language".to_string()),
+        ];
+        assert_eq!(blocks_with_options(&options, input), want);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::inline::inlines_with_options;
+use crate::options::Options;
+use crate::types::{Block, Block::Paragraph};
+use nom::{
+    error::{Error, ErrorKind},
+    Err, IResult,
+};
+
+/// A run of non-blank lines, up to a blank line or a line that starts a block
+/// able to interrupt a paragraph.  Leading whitespace of every line and
+/// trailing whitespace of the whole paragraph are stripped.
+pub fn paragraph<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Block> {
+    let mut lines = vec![];
+    let mut rest = input;
+    while !rest.is_empty() {
+        if !lines.is_empty() && super::interrupts_paragraph(options, rest) {
+            break;
+        }
+        let (line, remaining) = match rest.find('\n') {
+            Some(end) => (&rest[..end], &rest[end + 1..]),
+            None => (rest, ""),
+        };
+        if line.trim().is_empty() {
+            break;
+        }
+        lines.push(line.trim_start());
+        rest = remaining;
+    }
+    if lines.is_empty() {
+        return Err(Err::Error(Error::new(input, ErrorKind::TakeWhile1)));
+    }
+    let content = lines.join("\n");
+    Ok((rest, Paragraph(inlines_with_options(options, content.trim_end()))))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::types::Inline::{Emphasis, SoftBreak, Text};
+
+    #[test]
+    fn test_commonmark_ex189() {
+        let input = "aaa
+
+bbb";
+        let want = Paragraph(vec![Text("aaa".to_string())]);
+        let (input, result) = paragraph(&Options::default(), input).unwrap();
+        assert_eq!(input, "\nbbb");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_commonmark_ex190() {
+        let input = "aaa
+bbb
+
+ccc";
+        let want = Paragraph(vec![Text("aaa".to_string()), SoftBreak, Text("bbb".to_string())]);
+        let (input, result) = paragraph(&Options::default(), input).unwrap();
+        assert_eq!(input, "\nccc");
+        assert_eq!(want, result);
+    }
+
+    #[test]
+    fn test_commonmark_ex192() {
+        let input = "  aaa
+ *bbb*";
+        let want = Paragraph(vec![
+            Text("aaa".to_string()),
+            SoftBreak,
+            Emphasis(vec![Text("bbb".to_string())]),
+        ]);
+        let (input, result) = paragraph(&Options::default(), input).unwrap();
+        assert_eq!(input, "");
+        assert_eq!(want, result);
+    }
+}
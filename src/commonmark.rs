@@ -0,0 +1,711 @@
+use crate::inline::{
+    autolink_destination, decode_entity, extended_autolink, extended_autolink_boundary, is_punctuation, left_flanking,
+    right_flanking,
+};
+use crate::options::{Options, SingleTilde};
+use crate::render::prefix_lines;
+use crate::types::{Attributes, Block, Inline, ListItem};
+
+/// The length of the longest run of `ch` in `text`.
+fn longest_run(text: &str, ch: char) -> usize {
+    text.split(|c| c != ch).map(str::len).max().unwrap_or(0)
+}
+
+/// Backslash-escape the characters that end or alter a link destination or
+/// title.
+fn escape_link_part(text: &str, special: &str) -> String {
+    let mut escaped = String::new();
+    for ch in text.chars() {
+        if ch == '\\' || ch == '&' || special.contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+fn destination(url: &str) -> String {
+    if url.is_empty() || url.contains(|ch: char| ch.is_whitespace() || ch.is_control()) {
+        format!("<{}>", escape_link_part(url, "<>"))
+    } else {
+        escape_link_part(url, "()<>")
+    }
+}
+
+fn link_tail(url: &str, title: &Option<String>) -> String {
+    match title {
+        Some(title) => format!("({} \"{}\")", destination(url), escape_link_part(title, "\"")),
+        None => format!("({})", destination(url)),
+    }
+}
+
+fn attribute_value(value: &str) -> String {
+    if !value.is_empty() && !value.contains(|ch: char| ch.is_whitespace() || "{}\"'=".contains(ch)) {
+        value.to_string()
+    } else if value.contains('"') {
+        format!("'{}'", value)
+    } else {
+        format!("\"{}\"", value)
+    }
+}
+
+fn attributes(attributes: &Attributes) -> String {
+    let mut list = vec![];
+    if let Some(id) = &attributes.id {
+        list.push(format!("#{}", id));
+    }
+    list.extend(attributes.classes.iter().map(|class| format!(".{}", class)));
+    list.extend(attributes.pairs.iter().map(|(key, value)| format!("{}={}", key, attribute_value(value))));
+    if list.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", list.join(" "))
+    }
+}
+
+/// A code span with the shortest backtick fence that doesn't occur as a run
+/// of backticks in `code`, padded with spaces when the content would
+/// otherwise lose its edges.
+fn code_span(code: &str) -> String {
+    let runs: Vec<usize> = code.split(|ch| ch != '`').map(str::len).collect();
+    let length = (1..).find(|length| !runs.contains(length)).unwrap_or(1);
+    let fence = "`".repeat(length);
+    let padded = code.starts_with('`')
+        || code.ends_with('`')
+        || (code.starts_with(' ') && code.ends_with(' ') && !code.trim_matches(' ').is_empty());
+    if padded {
+        format!("{0} {1} {0}", fence, code)
+    } else {
+        format!("{0}{1}{0}", fence, code)
+    }
+}
+
+/// The character an inline starts with once written, to decide how the
+/// inline before it is written.  Emphasis is guessed to start with `*`.
+fn first_char(inline: &Inline) -> Option<char> {
+    match inline {
+        Inline::Text(text) | Inline::RawHTML(text) | Inline::Reference(_, text, _) | Inline::Abbreviation(text, _) => {
+            text.chars().next()
+        }
+        Inline::HardBreak | Inline::Literal(_) => Some('\\'),
+        Inline::SoftBreak => Some('\n'),
+        Inline::Code(..) => Some('`'),
+        Inline::Link(..) | Inline::RefLink(..) | Inline::FootnoteReference(_) | Inline::WikiLink(..) => Some('['),
+        Inline::Image(..) => Some('!'),
+        Inline::Emphasis(_) | Inline::Strong(_) => Some('*'),
+        Inline::Strike(_) | Inline::Subscript(_) => Some('~'),
+        Inline::Superscript(_) => Some('^'),
+        Inline::Highlight(_) => Some('='),
+        Inline::Formula(_) => Some('$'),
+        Inline::AutoLink(link) if autolink_destination(link) == format!("http://{}", link) => link.chars().next(),
+        Inline::AutoLink(_) => Some('<'),
+        Inline::Emoji(..) => Some(':'),
+    }
+}
+
+/// The punctuation character whose escape keeps an extended autolink at the
+/// start of `text` from being recognized: the `.` of `www.`, the `:` of a
+/// URL, or the first one of an email address.
+fn autolink_breaker(text: &str) -> Option<usize> {
+    text.char_indices().find(|(_, ch)| ".:@+-_".contains(*ch)).map(|(index, _)| index)
+}
+
+/// Put a formula between dollar signs, escaping each `$` that would
+/// otherwise end it.
+fn formula(formula: &str) -> String {
+    let mut escaped = String::new();
+    let mut backslash = false;
+    let mut prev = None;
+    for (index, ch) in formula.char_indices() {
+        let closes = !prev.is_some_and(char::is_whitespace)
+            && !formula[index + 1..].starts_with(|next: char| next.is_ascii_digit());
+        if ch == '$' && !backslash && closes {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+        backslash = ch == '\\' && !backslash;
+        prev = Some(ch);
+    }
+    format!("${}$", escaped)
+}
+
+/// `*` for emphasis, or `_` where a `*` right before or after it would make
+/// the two runs merge, as in `*a**b*` for two emphasized words in a row.
+/// Right inside a `*` within a word, a `_` would keep that `*` from opening
+/// or closing, as in `foo*__bar__*baz`, so the runs merge into `***` there.
+fn emphasis_delimiter(context: Context) -> &'static str {
+    let touches = |ch: char| context.before == Some(ch) || context.after == Some(ch);
+    let intraword = |ch: Option<char>| ch.is_some_and(char::is_alphanumeric);
+    if touches('*') && !touches('_') && !intraword(context.before) && !intraword(context.after) && !context.intraword {
+        "_"
+    } else {
+        "*"
+    }
+}
+
+struct Writer<'a> {
+    options: &'a Options,
+}
+
+/// Where an inline sequence is written: the characters right before and
+/// after it, `None` at the edges of a line, whether it ends a heading, where
+/// a closing `#` sequence or an attribute list would be taken from the text,
+/// whether it is in the brackets of a link, where a `[` would pair up with
+/// the closing bracket, and whether it is right inside a delimiter within a
+/// word.
+#[derive(Clone, Copy, Default)]
+struct Context {
+    before: Option<char>,
+    after: Option<char>,
+    heading: bool,
+    link: bool,
+    intraword: bool,
+}
+
+impl Writer<'_> {
+    fn inlines(&self, inlines: &[Inline], context: Context) -> String {
+        let mut markdown = String::new();
+        for (index, inline) in inlines.iter().enumerate() {
+            let inline_context = Context {
+                before: markdown.chars().last().or(context.before),
+                after: inlines.get(index + 1).map_or(context.after, first_char),
+                heading: context.heading && index + 1 == inlines.len(),
+                link: context.link,
+                intraword: context.intraword && (index == 0 || index + 1 == inlines.len()),
+            };
+            markdown.push_str(&self.inline(inline, inline_context));
+        }
+        markdown
+    }
+
+    fn inline(&self, inline: &Inline, context: Context) -> String {
+        let wrapped = |delimiter: &str, children: &[Inline]| {
+            let inner = Context {
+                before: delimiter.chars().last(),
+                after: delimiter.chars().next(),
+                heading: false,
+                link: context.link,
+                intraword: context.intraword
+                    || context.before.is_some_and(char::is_alphanumeric)
+                    || context.after.is_some_and(char::is_alphanumeric),
+            };
+            format!("{0}{1}{0}", delimiter, self.inlines(children, inner))
+        };
+        let bracketed = |children: &[Inline]| {
+            self.inlines(children, Context { before: Some('['), after: Some(']'), link: true, ..Context::default() })
+        };
+        match inline {
+            Inline::HardBreak => "\\\n".to_string(),
+            Inline::SoftBreak => "\n".to_string(),
+            Inline::Text(text) | Inline::Reference(_, text, _) | Inline::Abbreviation(text, _) => self.escape(text, context),
+            Inline::Literal(ch) => format!("\\{}", ch),
+            // No code span is empty, so there is nothing to write.
+            Inline::Code(code, _) if code.is_empty() => String::new(),
+            Inline::Code(code, attrs) => format!("{}{}", code_span(code), attributes(attrs)),
+            Inline::Link(children, url, title, attrs) => {
+                format!("[{}]{}{}", bracketed(children), link_tail(url, title), attributes(attrs))
+            }
+            Inline::RefLink(children, url, title) => {
+                let title = Some(title.clone()).filter(|title| !title.is_empty());
+                format!("[{}]{}", bracketed(children), link_tail(url, &title))
+            }
+            Inline::Image(children, url, title, attrs) => {
+                format!("![{}]{}{}", bracketed(children), link_tail(url, title), attributes(attrs))
+            }
+            Inline::Emphasis(children) => wrapped(emphasis_delimiter(context), children),
+            Inline::Strong(children) if emphasis_delimiter(context) == "_" => wrapped("__", children),
+            Inline::Strong(children) => wrapped("**", children),
+            Inline::Strike(children) => wrapped("~~", children),
+            Inline::Subscript(children) => wrapped("~", children),
+            Inline::Superscript(children) => wrapped("^", children),
+            Inline::Highlight(children) => wrapped("==", children),
+            Inline::Formula(content) => formula(content),
+            // `www.` links are only recognized without the angle brackets.
+            Inline::AutoLink(link) if autolink_destination(link) == format!("http://{}", link) => link.clone(),
+            Inline::AutoLink(link) => format!("<{}>", link),
+            Inline::RawHTML(html) => html.clone(),
+            Inline::Emoji(shortcode, _) => format!(":{}:", shortcode),
+            Inline::FootnoteReference(label) => format!("[^{}]", label),
+            Inline::WikiLink(target, anchor, label) => {
+                let mut link = format!("[[{}", target);
+                if let Some(anchor) = anchor {
+                    link.push_str(&format!("#{}", anchor));
+                }
+                if let Some(label) = label {
+                    link.push_str(&format!("|{}", label));
+                }
+                link.push_str("]]");
+                link
+            }
+        }
+    }
+
+    /// Backslash-escape the characters of `text` that would start or close
+    /// markup where they are written.  Whitespace at the start or the end of
+    /// a line, which parsing strips, and line endings, which would split the
+    /// text, become character references.
+    fn escape(&self, text: &str, context: Context) -> String {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let prev = |i: usize| if i == 0 { context.before } else { Some(chars[i - 1].1) };
+        let next = |i: usize| chars.get(i + 1).map(|&(_, ch)| ch).or(context.after);
+        let line_start = |i: usize| prev(i).is_none_or(|ch| ch == '\n');
+        let mut marks = vec![false; chars.len()];
+        let mut i = 0;
+        while i < chars.len() {
+            let (index, ch) = chars[i];
+            let rest = &text[index..];
+            let end = if "*_~=^-.#".contains(ch) {
+                i + chars[i..].iter().take_while(|&&(_, c)| c == ch).count()
+            } else {
+                i + 1
+            };
+            let (before, after) = (prev(i), next(end - 1));
+            let (left, right) = (left_flanking(before, after), right_flanking(before, after));
+            let escaped = match ch {
+                '\\' => after.is_some_and(|next| next.is_ascii_punctuation() || next == '\n'),
+                '`' | ']' => true,
+                '[' => context.link,
+                '*' => left || right || line_start(i),
+                '_' => {
+                    (left && (!right || before.is_some_and(is_punctuation)))
+                        || (right && (!left || after.is_some_and(is_punctuation)))
+                }
+                '~' => left || right || line_start(i) || self.options.single_tilde == SingleTilde::Subscript,
+                '=' => line_start(i) || (self.options.highlight && (left || right)),
+                '^' => self.options.superscript,
+                '-' => line_start(i) || (self.options.smart_punctuation && end - i > 1),
+                '.' => self.options.smart_punctuation && end - i > 2,
+                '#' => {
+                    let closing = context.heading && end == chars.len() && context.after.is_none();
+                    line_start(i) || (closing && before.is_none_or(char::is_whitespace))
+                }
+                '+' | '>' => line_start(i),
+                '<' => {
+                    let closed = rest.contains('>') || context.after.is_some();
+                    closed && after.is_some_and(|next| next.is_ascii_alphabetic() || "/!?".contains(next))
+                }
+                '&' => decode_entity(rest).is_ok(),
+                '$' => self.options.math,
+                '{' => self.options.attributes && (context.heading || (i == 0 && matches!(before, Some('`') | Some(')')))),
+                '\'' | '"' => self.options.smart_punctuation,
+                ':' => self.options.emoji && after.is_some_and(|next| next.is_ascii_alphanumeric() || "+-_".contains(next)),
+                _ => false,
+            };
+            for mark in &mut marks[i..end] {
+                *mark |= escaped;
+            }
+            if ch.is_ascii_digit() && line_start(i) {
+                // `1.` or `1)` would start an ordered list.
+                let digits = chars[i..].iter().take_while(|(_, c)| c.is_ascii_digit()).count();
+                if let Some((_, '.')) | Some((_, ')')) = chars.get(i + digits) {
+                    marks[i + digits] = true;
+                }
+            }
+            i = end;
+        }
+        // Text that reads as an extended autolink, also once cut short by
+        // an escape, gets one of its characters escaped too.
+        for (i, &(index, ch)) in chars.iter().enumerate() {
+            let rest = &text[index..];
+            if !ch.is_alphanumeric() || !extended_autolink_boundary(prev(i), rest) {
+                continue;
+            }
+            let cut = match marks[i..].iter().position(|&mark| mark) {
+                Some(offset) => &text[index..chars[i + offset].0],
+                None => rest,
+            };
+            if extended_autolink(rest).is_ok() || extended_autolink(cut).is_ok() {
+                if let Some(offset) = autolink_breaker(rest) {
+                    marks[i + rest[..offset].chars().count()] = true;
+                }
+            }
+        }
+
+        let leading = if line_start(0) { text.len() - text.trim_start().len() } else { 0 };
+        let line_end = context.after.is_none_or(|ch| ch == '\n');
+        let trailing = if line_end { text.len() - text.trim_end().len() } else { 0 };
+        let mut escaped = String::new();
+        for (&(index, ch), &mark) in chars.iter().zip(&marks) {
+            if ch == '\n' || ch == '\r' || (ch.is_whitespace() && (index < leading || index >= text.len() - trailing)) {
+                escaped.push_str(&format!("&#{};", ch as u32));
+            } else {
+                if mark {
+                    escaped.push('\\');
+                }
+                escaped.push(ch);
+            }
+        }
+        escaped
+    }
+
+    fn code_block(&self, info: &Option<String>, code: &str, attrs: &Attributes, indented: bool) -> String {
+        let plain = *attrs == Attributes::default();
+        if info.is_none() && plain && indented && code.ends_with('\n') && !code.trim().is_empty() && !code.starts_with('\n') {
+            // The trailing blank lines belong to the code, so they are kept
+            // instead of the blank line between blocks.
+            let (content, newlines) = code.split_at(code.trim_end_matches('\n').len());
+            return format!("{}{}", prefix_lines(content, "    ", "    "), newlines);
+        }
+        let info = info.as_deref().unwrap_or("");
+        let info = if plain { info.to_string() } else { format!("{} {}", info, attributes(attrs)).trim_start().to_string() };
+        let fence = if info.contains('`') {
+            "~".repeat(longest_run(code, '~').max(2) + 1)
+        } else {
+            "`".repeat(longest_run(code, '`').max(2) + 1)
+        };
+        if code.is_empty() {
+            format!("{0}{1}\n{0}", fence, info)
+        } else {
+            format!("{0}{1}\n{2}\n{0}", fence, info, code)
+        }
+    }
+
+    fn list(&self, items: &[ListItem], tight: bool, bullet: char) -> String {
+        let separator = if tight { "\n" } else { "\n\n" };
+        let items: Vec<String> = items
+            .iter()
+            .map(|item| {
+                let (marker, blocks) = match item {
+                    ListItem::TaskListItem(blocks, true) => (format!("{} [x] ", bullet), blocks),
+                    ListItem::TaskListItem(blocks, false) => (format!("{} [ ] ", bullet), blocks),
+                    ListItem::OrderedListItem(blocks, number) => {
+                        let delimiter = if bullet == '-' { '.' } else { ')' };
+                        (format!("{}{} ", number, delimiter), blocks)
+                    }
+                    ListItem::UnorderedListItem(blocks) => (format!("{} ", bullet), blocks),
+                };
+                let content = self.blocks(blocks);
+                let content = content.trim_end_matches('\n');
+                if content.is_empty() {
+                    marker.trim_end().to_string()
+                } else {
+                    let indent = " ".repeat(if let ListItem::TaskListItem(..) = item { 2 } else { marker.len() });
+                    prefix_lines(content, &marker, &indent)
+                }
+            })
+            .collect();
+        items.join(separator)
+    }
+
+    /// Render `block`.  `previous` is the block before it, to keep the two
+    /// apart where they would merge.
+    fn block(&self, block: &Block, previous: Option<&Block>) -> String {
+        match block {
+            Block::Paragraph(inlines) => self.inlines(inlines, Context::default()),
+            Block::Header(inlines, level, attrs) => {
+                let content = self.inlines(inlines, Context { heading: true, ..Context::default() });
+                let mut heading = format!("{} {}", "#".repeat(*level), content);
+                if *attrs != Attributes::default() {
+                    heading.push_str(&format!(" {}", attributes(attrs)));
+                }
+                heading.trim_end().to_string()
+            }
+            Block::Blockquote(blocks) => prefix_lines(self.blocks(blocks).trim_end_matches('\n'), "> ", "> "),
+            Block::CodeBlock(info, code, attrs) => {
+                let indented = !matches!(previous, Some(Block::List(..)) | Some(Block::FootnoteDefinition(..)));
+                self.code_block(info, code, attrs, indented)
+            }
+            Block::LinkRefence(label, url, Some(title)) => {
+                format!("[{}]: {} \"{}\"", label, destination(url), escape_link_part(title, "\""))
+            }
+            Block::LinkRefence(label, url, None) => format!("[{}]: {}", label, destination(url)),
+            Block::List(items, list_type, tight) => {
+                // Two lists in a row are told apart by their bullet or
+                // delimiter.
+                let alternate = matches!(previous, Some(Block::List(_, previous_type, _)) if previous_type == list_type);
+                let bullet = if alternate { '*' } else { '-' };
+                self.list(items, *tight, bullet)
+            }
+            Block::Formula(formula) => format!("$$\n{}\n$$", formula),
+            Block::HorizontalRule => "***".to_string(),
+            Block::FootnoteDefinition(label, blocks) => {
+                prefix_lines(self.blocks(blocks).trim_end_matches('\n'), &format!("[^{}]: ", label), "    ")
+            }
+            Block::AbbreviationDefinition(abbreviation, title) => format!("*[{}]: {}", abbreviation, title),
+        }
+    }
+
+    fn blocks(&self, blocks: &[Block]) -> String {
+        let mut markdown = String::new();
+        let mut previous = None;
+        for block in blocks {
+            if !markdown.is_empty() && !markdown.ends_with('\n') {
+                markdown.push_str("\n\n");
+            }
+            markdown.push_str(&self.block(block, previous));
+            previous = Some(block);
+        }
+        if !markdown.is_empty() && !markdown.ends_with('\n') {
+            markdown.push('\n');
+        }
+        markdown
+    }
+}
+
+/// Render `blocks` as CommonMark, such that parsing the result gives back
+/// the same blocks, with escaped characters as `Inline::Literal`.
+pub fn render(blocks: &[Block]) -> String {
+    render_with_options(&Options::default(), blocks)
+}
+
+/// Like `render`, for blocks parsed with `options`: text that would read as
+/// one of the enabled extensions is escaped too.
+pub fn render_with_options(options: &Options, blocks: &[Block]) -> String {
+    Writer { options }.blocks(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::{blocks, blocks_with_options, map_inlines};
+    use crate::options::SingleTilde;
+    use crate::spec::{examples, COMMONMARK, GFM};
+    use crate::types::ListType;
+    use crate::types::Block::{CodeBlock, List, Paragraph};
+    use crate::types::Inline::{Emphasis, Strong, Text};
+
+    /// Escaped characters come back as `Inline::Literal`, so they are
+    /// compared as text.
+    fn literals_as_text(inlines: Vec<Inline>) -> Vec<Inline> {
+        let mut result: Vec<Inline> = vec![];
+        for inline in inlines {
+            let inline = match inline {
+                Inline::Literal(ch) => Inline::Text(ch.to_string()),
+                Inline::Link(children, url, title, attributes) => {
+                    Inline::Link(literals_as_text(children), url, title, attributes)
+                }
+                Inline::Image(children, url, title, attributes) => {
+                    Inline::Image(literals_as_text(children), url, title, attributes)
+                }
+                Inline::RefLink(children, url, title) => Inline::RefLink(literals_as_text(children), url, title),
+                Inline::Emphasis(children) => Inline::Emphasis(literals_as_text(children)),
+                Inline::Strong(children) => Inline::Strong(literals_as_text(children)),
+                Inline::Strike(children) => Inline::Strike(literals_as_text(children)),
+                Inline::Subscript(children) => Inline::Subscript(literals_as_text(children)),
+                Inline::Superscript(children) => Inline::Superscript(literals_as_text(children)),
+                Inline::Highlight(children) => Inline::Highlight(literals_as_text(children)),
+                inline => inline,
+            };
+            match (result.last_mut(), inline) {
+                (Some(Inline::Text(last)), Inline::Text(text)) => last.push_str(&text),
+                (_, inline) => result.push(inline),
+            }
+        }
+        result
+    }
+
+    fn parse(options: &Options, input: &str) -> Vec<Block> {
+        map_inlines(blocks_with_options(options, input), &literals_as_text)
+    }
+
+    fn round_trip(options: &Options, input: &str) -> String {
+        let parsed = blocks_with_options(options, input);
+        let markdown = render_with_options(options, &parsed);
+        assert_eq!(parse(options, &markdown), map_inlines(parsed, &literals_as_text), "{:?}", markdown);
+        markdown
+    }
+
+    fn text(content: &str) -> Inline {
+        Text(content.to_string())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "# Title
+
+Some *emphasis*, **strong**, `code`, ~~strike~~ and a [link](/url \"title\").
+
+![image](</my image.png>) <https://example.com> \\*not emphasized\\*
+hard\\
+break
+
+```rust
+fn main() {}
+```
+
+    indented
+
+$$
+x^2
+$$
+";
+        assert_eq!(round_trip(&Options { math: true, ..Options::default() }, input), input);
+    }
+
+    #[test]
+    fn test_unneeded_escapes() {
+        let input = "2 * 3 * 4, snake_case_name and a [bracket.\n";
+        assert_eq!(round_trip(&Options::default(), input), input);
+    }
+
+    #[test]
+    fn test_needed_escapes() {
+        let input = "&ast;not emphasized&ast; &amp;copy; &#35; heading\n";
+        let markdown = round_trip(&Options::default(), input);
+        assert_eq!(markdown, "\\*not emphasized\\* \\&copy; # heading\n");
+        let input = "# foo &#35;\n";
+        assert_eq!(round_trip(&Options::default(), input), "# foo \\#\n");
+        let input = "foo&#10;&#10;bar\n";
+        assert_eq!(round_trip(&Options::default(), input), input);
+    }
+
+    #[test]
+    fn test_code_fences() {
+        let code = vec![CodeBlock(Some("".to_string()), "```\n````".to_string(), Attributes::default())];
+        assert_eq!(render(&code), "`````\n```\n````\n`````\n");
+        assert_eq!(blocks(&render(&code)), code);
+        let code = CodeBlock(Some("a`b".to_string()), "~~~".to_string(), Attributes::default());
+        assert_eq!(render(&[code]), "~~~~a`b\n~~~\n~~~~\n");
+    }
+
+    #[test]
+    fn test_code_spans() {
+        let input = "`` ` `` ` `` ` ```a``b```\n";
+        let want = "`` ` `` ` `` ` `a``b`\n";
+        assert_eq!(round_trip(&Options::default(), input), want);
+    }
+
+    #[test]
+    fn test_indented_code_blank_lines() {
+        let input = "    foo\n\n    bar\n\n\nx\n";
+        assert_eq!(round_trip(&Options::default(), input), input);
+    }
+
+    #[test]
+    fn test_nested_emphasis() {
+        let cases = vec![
+            Emphasis(vec![Emphasis(vec![text("x")])]),
+            Strong(vec![Emphasis(vec![text("x")])]),
+            Emphasis(vec![Strong(vec![text("x")])]),
+            Strong(vec![Strong(vec![text("x")])]),
+        ];
+        for inline in cases {
+            let paragraph = vec![Paragraph(vec![inline])];
+            assert_eq!(blocks(&render(&paragraph)), paragraph);
+        }
+        let paragraph = vec![Paragraph(vec![text("foo"), Emphasis(vec![Strong(vec![text("bar")])]), text("baz")])];
+        assert_eq!(render(&paragraph), "foo***bar***baz\n");
+    }
+
+    #[test]
+    fn test_adjacent_emphasis() {
+        let emphasis = |content: &str| Emphasis(vec![text(content)]);
+        let strong = |content: &str| Strong(vec![text(content)]);
+        let cases = vec![
+            vec![emphasis("a"), emphasis("b")],
+            vec![strong("a"), strong("b")],
+            vec![emphasis("a"), strong("b")],
+            vec![strong("a"), emphasis("b")],
+            vec![text("x "), emphasis("a"), emphasis("b"), text(" y")],
+            vec![Strong(vec![text("x"), emphasis("a"), emphasis("b")])],
+        ];
+        for inlines in cases {
+            let paragraph = vec![Paragraph(inlines)];
+            assert_eq!(blocks(&render(&paragraph)), paragraph, "{:?}", render(&paragraph));
+        }
+        assert_eq!(render(&[Paragraph(vec![emphasis("a"), emphasis("b")])]), "_a_*b*\n");
+    }
+
+    #[test]
+    fn test_context_escapes() {
+        let cases = vec![
+            ("a*b", "a\\*b"),
+            ("2 * 3", "2 * 3"),
+            ("snake_case _x", "snake_case \\_x"),
+            ("- item", "\\- item"),
+            ("1. item", "1\\. item"),
+            ("# title", "\\# title"),
+            ("a [b] c", "a [b\\] c"),
+            ("`code`", "\\`code\\`"),
+            ("<b>", "\\<b>"),
+            ("www.example.com", "www\\.example.com"),
+            ("a\\", "a\\"),
+        ];
+        for (content, want) in cases {
+            let paragraph = vec![Paragraph(vec![text(content)])];
+            assert_eq!(render(&paragraph), format!("{}\n", want));
+        }
+        let options = Options { smart_punctuation: true, ..Options::default() };
+        assert_eq!(round_trip(&options, "\\\"a\\-\\-b\\.\\.\\.\n"), "\\\"a\\-\\-b\\.\\.\\.\n");
+    }
+
+    #[test]
+    fn test_formula_and_empty_code() {
+        let options = Options { math: true, ..Options::default() };
+        assert_eq!(round_trip(&options, "$a$5$ and $\\$$\n"), "$a$5$ and $\\$$\n");
+        let formula = vec![Paragraph(vec![Inline::Formula("a$b".to_string())])];
+        assert_eq!(render_with_options(&options, &formula), "$a\\$b$\n");
+        let code = vec![Paragraph(vec![text("a"), Inline::Code(String::new(), Attributes::default()), text("b")])];
+        assert_eq!(render(&code), "ab\n");
+    }
+
+    #[test]
+    fn test_extensions() {
+        let options = Options {
+            emoji: true,
+            footnotes: true,
+            wiki_links: true,
+            highlight: true,
+            attributes: true,
+            abbreviations: true,
+            ..Options::default()
+        };
+        let input = "# Setup {#setup .big}
+
+==Read== the [[Main Page#Intro|intro]] :tada: of HTML[^1].
+
+*[HTML]: Hyper Text Markup Language
+
+```rust {#main .numberLines}
+fn main() {}
+```
+
+[^1]: A note.
+";
+        assert_eq!(round_trip(&options, input), input);
+    }
+
+    #[test]
+    fn test_lists() {
+        let item = |content: &str, number| ListItem::OrderedListItem(vec![Paragraph(vec![text(content)])], number);
+        let mut lists = vec![List(vec![item("a", 3), item("b", 4)], ListType::OrderedList, true)];
+        assert_eq!(render(&lists), "3. a\n4. b\n");
+        lists.push(List(vec![item("a", 1), item("b", 2)], ListType::OrderedList, false));
+        assert_eq!(render(&lists), "3. a\n4. b\n\n1) a\n\n2) b\n");
+        let code = CodeBlock(None, "x\n".to_string(), Attributes::default());
+        let task = ListItem::TaskListItem(vec![Paragraph(vec![text("done")]), code], true);
+        let list = List(vec![task], ListType::TaskList, false);
+        assert_eq!(render(&[list]), "- [x] done\n\n      x\n");
+    }
+
+    #[test]
+    fn test_blockquote() {
+        let quote = Block::Blockquote(vec![Paragraph(vec![text("a")]), Paragraph(vec![text("b")])]);
+        assert_eq!(render(&[quote]), "> a\n>\n> b\n");
+    }
+
+    #[test]
+    fn test_spec_examples() {
+        let extensions = Options {
+            single_tilde: SingleTilde::Subscript,
+            superscript: true,
+            emoji: true,
+            footnotes: true,
+            wiki_links: true,
+            highlight: true,
+            smart_punctuation: true,
+            attributes: true,
+            abbreviations: true,
+            math: true,
+        };
+        for options in &[Options::default(), extensions] {
+            for example in examples(COMMONMARK).iter().chain(examples(GFM).iter()) {
+                let parsed = blocks_with_options(options, &example.markdown);
+                let markdown = render_with_options(options, &parsed);
+                let want = map_inlines(parsed, &literals_as_text);
+                assert_eq!(parse(options, &markdown), want, "example {}: {:?}", example.number, markdown);
+            }
+        }
+    }
+}
@@ -0,0 +1,686 @@
+use crate::block::Footnotes;
+use crate::inline::{autolink_destination, default_resolver, unescape, WikiLinkResolver};
+use crate::render::item_blocks;
+use crate::types::{Attributes, Block, Inline, ListItem, ListType};
+
+/// Escape `&`, `<`, `>` and `"` for use in text and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Percent-encode a link destination the way cmark does: characters that are
+/// allowed in a URL are kept, including `%` so that existing escapes survive,
+/// `&` and `'` become entities and everything else is encoded byte by byte.
+pub fn escape_href(url: &str) -> String {
+    let mut escaped = String::with_capacity(url.len());
+    for ch in url.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '\'' => escaped.push_str("&#x27;"),
+            ch if ch.is_ascii_alphanumeric() || "-_.+!*(),%#@?=;:/$~".contains(ch) => escaped.push(ch),
+            ch => {
+                let mut bytes = [0; 4];
+                for byte in ch.encode_utf8(&mut bytes).bytes() {
+                    escaped.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+    escaped
+}
+
+/// The plain text of `inlines`, as used for the `alt` attribute of images.
+fn plain_text(inlines: &[Inline]) -> String {
+    let mut text = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::HardBreak | Inline::SoftBreak => text.push('\n'),
+            Inline::Text(content) | Inline::Code(content, _) | Inline::Formula(content) | Inline::AutoLink(content) => {
+                text.push_str(content)
+            }
+            Inline::RawHTML(content) | Inline::Emoji(_, content) | Inline::Reference(_, content, _) => {
+                text.push_str(content)
+            }
+            Inline::Abbreviation(abbreviation, _) => text.push_str(abbreviation),
+            Inline::Literal(ch) => text.push(*ch),
+            Inline::Link(children, ..)
+            | Inline::RefLink(children, ..)
+            | Inline::Image(children, ..)
+            | Inline::Emphasis(children)
+            | Inline::Strong(children)
+            | Inline::Strike(children)
+            | Inline::Subscript(children)
+            | Inline::Superscript(children)
+            | Inline::Highlight(children) => text.push_str(&plain_text(children)),
+            Inline::FootnoteReference(label) => text.push_str(&format!("[^{}]", label)),
+            Inline::WikiLink(target, anchor, label) => text.push_str(&wiki_link_text(target, anchor, label)),
+        }
+    }
+    text
+}
+
+fn wiki_link_text(target: &str, anchor: &Option<String>, label: &Option<String>) -> String {
+    match (label, anchor) {
+        (Some(label), _) => label.clone(),
+        (None, Some(anchor)) if target.is_empty() => anchor.clone(),
+        (None, Some(anchor)) => format!("{}#{}", target, anchor),
+        (None, None) => target.to_string(),
+    }
+}
+
+/// The attributes of an element.  `class` pairs are merged into the
+/// classes, and the id wins over `id` pairs, of which only the first counts.
+fn attributes(attributes: &Attributes) -> String {
+    let mut html = String::new();
+    let pairs = attributes.pairs.iter().filter(|(key, _)| valid_attribute_name(key));
+    let first_id = pairs.clone().find(|(key, _)| key == "id").map(|(_, value)| value.as_str());
+    let id = attributes.id.as_deref().or(first_id);
+    if let Some(id) = id {
+        html.push_str(&format!(" id=\"{}\"", escape(id)));
+    }
+    let mut classes: Vec<&str> = attributes.classes.iter().map(String::as_str).collect();
+    classes.extend(pairs.clone().filter(|(key, _)| key == "class").map(|(_, value)| value.as_str()));
+    if !classes.is_empty() {
+        html.push_str(&format!(" class=\"{}\"", escape(&classes.join(" "))));
+    }
+    for (key, value) in pairs.filter(|(key, _)| key != "id" && key != "class") {
+        html.push_str(&format!(" {}=\"{}\"", key, escape(value)));
+    }
+    html
+}
+
+/// Whether `name` matches `[A-Za-z_:][A-Za-z0-9_.:-]*`, so that it can be
+/// written as an attribute name.  Other keys are dropped.
+fn valid_attribute_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || "_.:-".contains(c))
+}
+
+fn title(title: &Option<String>) -> String {
+    match title {
+        Some(title) => format!(" title=\"{}\"", escape(title)),
+        None => String::new(),
+    }
+}
+
+/// Start a new line unless the output is empty or already at one.
+fn cr(html: &mut String) {
+    if !html.is_empty() && !html.ends_with('\n') {
+        html.push('\n');
+    }
+}
+
+fn footnote_definitions<'a>(blocks: &'a [Block], definitions: &mut Vec<(&'a str, &'a [Block])>) {
+    for block in blocks {
+        match block {
+            Block::FootnoteDefinition(label, content) => {
+                if !definitions.iter().any(|(defined, _)| defined == label) {
+                    definitions.push((label, content));
+                }
+                footnote_definitions(content, definitions);
+            }
+            Block::Blockquote(content) => footnote_definitions(content, definitions),
+            Block::List(items, ..) => {
+                for item in items {
+                    footnote_definitions(item_blocks(item), definitions);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Renderer<'a> {
+    wiki_links: &'a dyn WikiLinkResolver,
+    footnotes: Footnotes,
+}
+
+impl Renderer<'_> {
+    fn inlines(&self, html: &mut String, inlines: &[Inline]) {
+        for inline in inlines {
+            self.inline(html, inline);
+        }
+    }
+
+    fn wrapped(&self, html: &mut String, tag: &str, children: &[Inline]) {
+        html.push_str(&format!("<{}>", tag));
+        self.inlines(html, children);
+        html.push_str(&format!("</{}>", tag));
+    }
+
+    fn inline(&self, html: &mut String, inline: &Inline) {
+        match inline {
+            Inline::HardBreak => html.push_str("<br />\n"),
+            Inline::SoftBreak => html.push('\n'),
+            Inline::Text(text) => html.push_str(&escape(text)),
+            Inline::Literal(ch) => html.push_str(&escape(&ch.to_string())),
+            Inline::Code(code, attrs) => {
+                html.push_str(&format!("<code{}>{}</code>", attributes(attrs), escape(code)));
+            }
+            Inline::Link(children, destination, link_title, attrs) => {
+                html.push_str(&format!(
+                    "<a href=\"{}\"{}{}>",
+                    escape_href(destination),
+                    title(link_title),
+                    attributes(attrs)
+                ));
+                self.inlines(html, children);
+                html.push_str("</a>");
+            }
+            Inline::RefLink(children, destination, link_title) => {
+                let link_title = Some(link_title.clone()).filter(|link_title| !link_title.is_empty());
+                html.push_str(&format!("<a href=\"{}\"{}>", escape_href(destination), title(&link_title)));
+                self.inlines(html, children);
+                html.push_str("</a>");
+            }
+            Inline::Image(children, destination, image_title, attrs) => {
+                html.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\"{}{} />",
+                    escape_href(destination),
+                    escape(&plain_text(children)),
+                    title(image_title),
+                    attributes(attrs)
+                ));
+            }
+            Inline::Emphasis(children) => self.wrapped(html, "em", children),
+            Inline::Strong(children) => self.wrapped(html, "strong", children),
+            Inline::Strike(children) => self.wrapped(html, "del", children),
+            Inline::Subscript(children) => self.wrapped(html, "sub", children),
+            Inline::Superscript(children) => self.wrapped(html, "sup", children),
+            Inline::Highlight(children) => self.wrapped(html, "mark", children),
+            Inline::Formula(formula) => {
+                html.push_str(&format!("<span class=\"math inline\">\\({}\\)</span>", escape(formula)));
+            }
+            Inline::AutoLink(link) => {
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_href(&autolink_destination(link)),
+                    escape(link)
+                ));
+            }
+            Inline::RawHTML(raw) => html.push_str(raw),
+            Inline::Emoji(_, emoji) => html.push_str(emoji),
+            Inline::FootnoteReference(label) => match self.footnotes.number(label) {
+                Some(number) => html.push_str(&format!(
+                    "<sup class=\"footnote-ref\"><a href=\"#fn{0}\" id=\"fnref{0}\">{0}</a></sup>",
+                    number
+                )),
+                None => html.push_str(&escape(&format!("[^{}]", label))),
+            },
+            Inline::WikiLink(target, anchor, label) => {
+                let url = self.wiki_links.resolve(target, anchor.as_deref());
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_href(&url),
+                    escape(&wiki_link_text(target, anchor, label))
+                ));
+            }
+            Inline::Reference(_, text, url) => {
+                html.push_str(&format!("<a href=\"{}\">{}</a>", escape_href(url), escape(text)));
+            }
+            Inline::Abbreviation(abbreviation, abbreviation_title) => {
+                html.push_str(&format!(
+                    "<abbr title=\"{}\">{}</abbr>",
+                    escape(abbreviation_title),
+                    escape(abbreviation)
+                ));
+            }
+        }
+    }
+
+    fn blocks(&self, html: &mut String, blocks: &[Block], tight: bool) {
+        for block in blocks {
+            self.block(html, block, tight);
+        }
+    }
+
+    /// Render `block`.  In a tight list, paragraphs are rendered without
+    /// their `<p>` tags.
+    fn block(&self, html: &mut String, block: &Block, tight: bool) {
+        match block {
+            Block::Paragraph(inlines) if tight => self.inlines(html, inlines),
+            Block::Paragraph(inlines) => {
+                cr(html);
+                html.push_str("<p>");
+                self.inlines(html, inlines);
+                html.push_str("</p>\n");
+            }
+            Block::Header(inlines, level, attrs) => {
+                cr(html);
+                html.push_str(&format!("<h{}{}>", level, attributes(attrs)));
+                self.inlines(html, inlines);
+                html.push_str(&format!("</h{}>\n", level));
+            }
+            Block::Blockquote(blocks) => {
+                cr(html);
+                html.push_str("<blockquote>\n");
+                self.blocks(html, blocks, false);
+                cr(html);
+                html.push_str("</blockquote>\n");
+            }
+            Block::CodeBlock(info, code, attrs) => {
+                cr(html);
+                html.push_str(&format!("<pre{}><code", attributes(attrs)));
+                if let Some(language) = info.as_deref().and_then(|info| info.split_whitespace().next()) {
+                    html.push_str(&format!(" class=\"language-{}\"", escape(&unescape(language))));
+                }
+                html.push('>');
+                html.push_str(&escape(code));
+                if !code.is_empty() && !code.ends_with('\n') {
+                    html.push('\n');
+                }
+                html.push_str("</code></pre>\n");
+            }
+            Block::List(items, list_type, tight) => {
+                cr(html);
+                let start = match items.first() {
+                    Some(ListItem::OrderedListItem(_, start)) => *start,
+                    _ => 1,
+                };
+                let tag = match list_type {
+                    ListType::OrderedList if start != 1 => {
+                        html.push_str(&format!("<ol start=\"{}\">\n", start));
+                        "ol"
+                    }
+                    ListType::OrderedList => {
+                        html.push_str("<ol>\n");
+                        "ol"
+                    }
+                    ListType::TaskList | ListType::UnorderedList => {
+                        html.push_str("<ul>\n");
+                        "ul"
+                    }
+                };
+                for item in items {
+                    html.push_str("<li>");
+                    match item {
+                        ListItem::TaskListItem(_, true) => {
+                            html.push_str("<input type=\"checkbox\" checked=\"\" disabled=\"\" /> ")
+                        }
+                        ListItem::TaskListItem(_, false) => html.push_str("<input type=\"checkbox\" disabled=\"\" /> "),
+                        _ => {}
+                    }
+                    self.blocks(html, item_blocks(item), *tight);
+                    html.push_str("</li>\n");
+                }
+                html.push_str(&format!("</{}>\n", tag));
+            }
+            Block::Formula(formula) => {
+                cr(html);
+                html.push_str(&format!("<p><span class=\"math display\">\\[{}\\]</span></p>\n", escape(formula)));
+            }
+            Block::HorizontalRule => {
+                cr(html);
+                html.push_str("<hr />\n");
+            }
+            Block::LinkRefence(..) | Block::FootnoteDefinition(..) | Block::AbbreviationDefinition(..) => {}
+        }
+    }
+
+    /// The footnote section at the end of the document, in the order of the
+    /// first reference to each footnote.
+    fn footnotes(&self, html: &mut String, blocks: &[Block]) {
+        if self.footnotes.order.is_empty() {
+            return;
+        }
+        let mut definitions = vec![];
+        footnote_definitions(blocks, &mut definitions);
+        cr(html);
+        html.push_str("<section class=\"footnotes\">\n<ol>\n");
+        for (index, label) in self.footnotes.order.iter().enumerate() {
+            let number = index + 1;
+            html.push_str(&format!("<li id=\"fn{}\">\n", number));
+            if let Some((_, content)) = definitions.iter().find(|(defined, _)| defined == label) {
+                self.blocks(html, content, false);
+            }
+            let backref = format!("<a href=\"#fnref{}\" class=\"footnote-backref\">↩</a>", number);
+            if html.ends_with("</p>\n") {
+                html.truncate(html.len() - "</p>\n".len());
+                html.push_str(&format!(" {}</p>\n", backref));
+            } else {
+                cr(html);
+                html.push_str(&format!("{}\n", backref));
+            }
+            html.push_str("</li>\n");
+        }
+        html.push_str("</ol>\n</section>\n");
+    }
+}
+
+/// Render `blocks` as HTML, in the same form as the examples of the CommonMark
+/// and GFM specs.  Footnotes are collected in a section at the end.
+pub fn render(blocks: &[Block]) -> String {
+    render_with_resolver(blocks, &default_resolver)
+}
+
+/// Like `render`, with wiki links resolved to URLs by `resolver`.
+pub fn render_with_resolver(blocks: &[Block], resolver: &dyn WikiLinkResolver) -> String {
+    let renderer = Renderer { wiki_links: resolver, footnotes: Footnotes::new(blocks) };
+    let mut html = String::new();
+    renderer.blocks(&mut html, blocks, false);
+    renderer.footnotes(&mut html, blocks);
+    html
+}
+
+/// Render a sequence of inlines as HTML.
+pub fn render_inlines(inlines: &[Inline]) -> String {
+    let renderer = Renderer { wiki_links: &default_resolver, footnotes: Footnotes::new(&[]) };
+    let mut html = String::new();
+    renderer.inlines(&mut html, inlines);
+    html
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::{blocks, blocks_with_options};
+    use crate::options::Options;
+    use crate::types::Block::{List, Paragraph};
+    use crate::types::Inline::Text;
+
+    fn markdown(input: &str) -> String {
+        render(&blocks(input))
+    }
+
+    fn item(text: &str) -> ListItem {
+        ListItem::UnorderedListItem(vec![Paragraph(vec![Text(text.to_string())])])
+    }
+
+    #[test]
+    fn test_commonmark_ex112() {
+        let input = "```ruby
+def foo(x)
+  return 3
+end
+```
+";
+        let want = "<pre><code class=\"language-ruby\">def foo(x)
+  return 3
+end
+</code></pre>
+";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex113() {
+        let input = "~~~~    ruby startline=3 $%@#$
+def foo(x)
+  return 3
+end
+~~~~~~~
+";
+        let want = "<pre><code class=\"language-ruby\">def foo(x)
+  return 3
+end
+</code></pre>
+";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex311() {
+        let input = "&nbsp; &amp; &copy; &AElig; &Dcaron;
+&frac34; &HilbertSpace; &DifferentialD;
+&ClockwiseContourIntegral; &ngE;
+";
+        let want = "<p>\u{a0} &amp; © Æ Ď
+¾ ℋ ⅆ
+∲ ≧̸</p>
+";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex502() {
+        let input = "[link](/url \"title \\\"&quot;\")\n";
+        let want = "<p><a href=\"/url\" title=\"title &quot;&quot;\">link</a></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex503() {
+        let input = "[link](/url\u{a0}\"title\")\n";
+        let want = "<p><a href=\"/url%C2%A0%22title%22\">link</a></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex504() {
+        let input = "[link](/url \"title \"and\" title\")\n";
+        let want = "<p>[link](/url &quot;title &quot;and&quot; title&quot;)</p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex517() {
+        let input = "*[foo*](/uri)\n";
+        let want = "<p>*<a href=\"/uri\">foo*</a></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex570() {
+        let input = "![foo ![bar](/url)](/url2)\n";
+        let want = "<p><img src=\"/url2\" alt=\"foo bar\" /></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex571() {
+        let input = "![foo [bar](/url)](/url2)\n";
+        let want = "<p><img src=\"/url2\" alt=\"foo bar\" /></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex575() {
+        let input = "My ![foo bar](/path/to/train.jpg  \"title\"   )\n";
+        let want = "<p>My <img src=\"/path/to/train.jpg\" alt=\"foo bar\" title=\"title\" /></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex593() {
+        let input = "<MAILTO:FOO@BAR.BAZ>\n";
+        let want = "<p><a href=\"MAILTO:FOO@BAR.BAZ\">MAILTO:FOO@BAR.BAZ</a></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex599() {
+        let input = "<http://example.com/\\[\\>\n";
+        let want = "<p><a href=\"http://example.com/%5C%5B%5C\">http://example.com/\\[\\</a></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex600() {
+        let input = "<foo@bar.example.com>\n";
+        let want = "<p><a href=\"mailto:foo@bar.example.com\">foo@bar.example.com</a></p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex630() {
+        let input = "foo  \nbaz\n";
+        let want = "<p>foo<br />\nbaz</p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_commonmark_ex31() {
+        let list = List(
+            vec![item("Foo"), ListItem::UnorderedListItem(vec![Block::HorizontalRule])],
+            ListType::UnorderedList,
+            true,
+        );
+        let want = "<ul>
+<li>Foo</li>
+<li>
+<hr />
+</li>
+</ul>
+";
+        assert_eq!(render(&[list]), want);
+    }
+
+    #[test]
+    fn test_loose_list() {
+        let list = List(vec![item("a"), item("b")], ListType::UnorderedList, false);
+        let want = "<ul>
+<li>
+<p>a</p>
+</li>
+<li>
+<p>b</p>
+</li>
+</ul>
+";
+        assert_eq!(render(&[list]), want);
+    }
+
+    #[test]
+    fn test_ordered_list_start() {
+        let item = |number| ListItem::OrderedListItem(vec![Paragraph(vec![Text("x".to_string())])], number);
+        let list = List(vec![item(3), item(4)], ListType::OrderedList, true);
+        let want = "<ol start=\"3\">
+<li>x</li>
+<li>x</li>
+</ol>
+";
+        assert_eq!(render(&[list]), want);
+    }
+
+    #[test]
+    fn test_nested_tight_list() {
+        let inner = List(vec![item("b")], ListType::UnorderedList, true);
+        let outer = ListItem::UnorderedListItem(vec![Paragraph(vec![Text("a".to_string())]), inner]);
+        let list = List(vec![outer], ListType::UnorderedList, true);
+        let want = "<ul>
+<li>a
+<ul>
+<li>b</li>
+</ul>
+</li>
+</ul>
+";
+        assert_eq!(render(&[list]), want);
+    }
+
+    #[test]
+    fn test_gfm_ex279() {
+        let task = |text: &str, checked| ListItem::TaskListItem(vec![Paragraph(vec![Text(text.to_string())])], checked);
+        let list = List(vec![task("foo", false), task("bar", true)], ListType::TaskList, true);
+        let want = "<ul>
+<li><input type=\"checkbox\" disabled=\"\" /> foo</li>
+<li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> bar</li>
+</ul>
+";
+        assert_eq!(render(&[list]), want);
+    }
+
+    #[test]
+    fn test_gfm_ex491() {
+        let input = "~~Hi~~ Hello, world!\n";
+        let want = "<p><del>Hi</del> Hello, world!</p>\n";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_attributes() {
+        let options = Options { attributes: true, ..Options::default() };
+        let input = "# Title {#top .big lang=en}\n\n`code`{.rust}\n\n```rust {#main .numberLines}\nfn main() {}\n```\n";
+        let want = "<h1 id=\"top\" class=\"big\" lang=\"en\">Title</h1>
+<p><code class=\"rust\">code</code></p>
+<pre id=\"main\" class=\"numberLines\"><code class=\"language-rust\">fn main() {}
+</code></pre>
+";
+        assert_eq!(render(&blocks_with_options(&options, input)), want);
+        let attributes = Attributes {
+            id: None,
+            classes: vec![],
+            pairs: vec![
+                ("onclick=\"alert(1)\" x".to_string(), "y".to_string()),
+                ("1a".to_string(), "y".to_string()),
+                ("data-x".to_string(), "<y>".to_string()),
+            ],
+        };
+        let block = Block::Header(vec![Text("Title".to_string())], 2, attributes);
+        assert_eq!(render(&[block]), "<h2 data-x=\"&lt;y&gt;\">Title</h2>\n");
+        let input = "# Title {#top .big class=small id=other}\n\n# Other {id=first id=second class=a class=b}\n";
+        let want = "<h1 id=\"top\" class=\"big small\">Title</h1>\n<h1 id=\"first\" class=\"a b\">Other</h1>\n";
+        assert_eq!(render(&blocks_with_options(&options, input)), want);
+    }
+
+    #[test]
+    fn test_info_string_escapes() {
+        assert_eq!(markdown("``` foo\\+bar\nfoo\n```\n"), "<pre><code class=\"language-foo+bar\">foo\n</code></pre>\n");
+        let want = "<pre><code class=\"language-föö\">foo\n</code></pre>\n";
+        assert_eq!(markdown("``` f&ouml;&ouml;\nfoo\n```\n"), want);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let options = Options { footnotes: true, ..Options::default() };
+        let input = "Here[^note] and [^missing].
+
+[^note]: The note.
+";
+        let want = "<p>Here<sup class=\"footnote-ref\"><a href=\"#fn1\" id=\"fnref1\">1</a></sup> and [^missing].</p>
+<section class=\"footnotes\">
+<ol>
+<li id=\"fn1\">
+<p>The note. <a href=\"#fnref1\" class=\"footnote-backref\">↩</a></p>
+</li>
+</ol>
+</section>
+";
+        assert_eq!(render(&blocks_with_options(&options, input)), want);
+    }
+
+    #[test]
+    fn test_extensions() {
+        let options = Options {
+            emoji: true,
+            highlight: true,
+            abbreviations: true,
+            ..Options::default()
+        };
+        let input = "==HTML== :tada:
+
+*[HTML]: Hyper Text Markup Language
+";
+        let want = "<p><mark><abbr title=\"Hyper Text Markup Language\">HTML</abbr></mark> 🎉</p>\n";
+        assert_eq!(render(&blocks_with_options(&options, input)), want);
+    }
+
+    #[test]
+    fn test_wiki_links() {
+        let options = Options { wiki_links: true, ..Options::default() };
+        let blocks = blocks_with_options(&options, "[[Main Page#See also|more]] [[Other]]\n");
+        let want = "<p><a href=\"Main_Page#See_also\">more</a> <a href=\"Other\">Other</a></p>\n";
+        assert_eq!(render(&blocks), want);
+        let resolver = |target: &str, _: Option<&str>| format!("/wiki/{}", target.to_lowercase());
+        let want = "<p><a href=\"/wiki/main%20page\">more</a> <a href=\"/wiki/other\">Other</a></p>\n";
+        assert_eq!(render_with_resolver(&blocks, &resolver), want);
+    }
+
+    #[test]
+    fn test_escape_href() {
+        assert_eq!(escape_href("/a b&c'[d]"), "/a%20b&amp;c&#x27;%5Bd%5D");
+        assert_eq!(escape_href("foo%20b\u{e4}"), "foo%20b%C3%A4");
+    }
+}
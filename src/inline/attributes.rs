@@ -0,0 +1,94 @@
+use nom::{
+  branch::alt,
+  bytes::complete::{take_while, take_while1},
+  character::complete::char,
+  combinator::map,
+  multi::separated_list0,
+  sequence::{delimited, preceded, separated_pair},
+  IResult,
+};
+use crate::types::Attributes;
+
+enum Attribute {
+  Id(String),
+  Class(String),
+  Pair(String, String),
+}
+
+fn whitespace(input: &str) -> IResult<&str, &str> {
+  take_while(|ch: char| ch == ' ' || ch == '\t' || ch == '\n')(input)
+}
+
+fn whitespace1(input: &str) -> IResult<&str, &str> {
+  take_while1(|ch: char| ch == ' ' || ch == '\t' || ch == '\n')(input)
+}
+
+fn identifier(input: &str) -> IResult<&str, &str> {
+  take_while1(|ch: char| ch.is_alphanumeric() || "-_:.".contains(ch))(input)
+}
+
+fn value(input: &str) -> IResult<&str, &str> {
+  alt((
+    delimited(char('"'), take_while(|ch| ch != '"'), char('"')),
+    delimited(char('\''), take_while(|ch| ch != '\''), char('\'')),
+    take_while1(|ch: char| !ch.is_whitespace() && !"{}\"'=".contains(ch)),
+  ))(input)
+}
+
+fn attribute(input: &str) -> IResult<&str, Attribute> {
+  alt((
+    map(preceded(char('#'), identifier), |id: &str| Attribute::Id(id.to_string())),
+    map(preceded(char('.'), identifier), |class: &str| Attribute::Class(class.to_string())),
+    map(separated_pair(identifier, char('='), value), |(key, value): (&str, &str)| {
+      Attribute::Pair(key.to_string(), value.to_string())
+    }),
+  ))(input)
+}
+
+/// Parse an attribute list such as `{#install .tip width=50%}`: an id after
+/// `#`, classes after `.`, and `key=value` pairs whose value may be quoted.
+/// When several ids are given, the last one wins.
+pub fn attributes(input: &str) -> IResult<&str, Attributes> {
+  let (input, list) = delimited(
+    char('{'),
+    delimited(whitespace, separated_list0(whitespace1, attribute), whitespace),
+    char('}'),
+  )(input)?;
+  let mut attributes = Attributes::default();
+  for attribute in list {
+    match attribute {
+      Attribute::Id(id) => attributes.id = Some(id),
+      Attribute::Class(class) => attributes.classes.push(class),
+      Attribute::Pair(key, value) => attributes.pairs.push((key, value)),
+    }
+  }
+  Ok((input, attributes))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_attributes() {
+    let input = "{#install .tip .wide width=50% title=\"Read me\"} rest";
+    let want = Attributes {
+      id: Some("install".to_string()),
+      classes: vec!["tip".to_string(), "wide".to_string()],
+      pairs: vec![("width".to_string(), "50%".to_string()), ("title".to_string(), "Read me".to_string())],
+    };
+    assert_eq!(attributes(input), Ok((" rest", want)));
+  }
+
+  #[test]
+  fn test_attributes_empty() {
+    assert_eq!(attributes("{ }"), Ok(("", Attributes::default())));
+  }
+
+  #[test]
+  fn test_attributes_invalid() {
+    for input in ["{#}", "{.rust", "{width=}", "{not an attribute}", "{#a#b}"] {
+      assert!(attributes(input).is_err(), "{}", input);
+    }
+  }
+}
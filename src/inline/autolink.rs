@@ -0,0 +1,367 @@
+use nom::{
+  branch::alt,
+  bytes::complete::{tag_no_case, take_while, take_while1, take_while_m_n},
+  character::complete::{char, satisfy},
+  combinator::{recognize, verify},
+  error::{Error, ErrorKind},
+  multi::many0,
+  sequence::{delimited, pair, tuple},
+  Err, IResult,
+};
+use crate::types::{Inline, Inline::AutoLink};
+
+fn scheme(input: &str) -> IResult<&str, &str> {
+  recognize(pair(
+    satisfy(|ch| ch.is_ascii_alphabetic()),
+    take_while_m_n(1, 31, |ch: char| ch.is_ascii_alphanumeric() || "+.-".contains(ch)),
+  ))(input)
+}
+
+fn absolute_uri(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((
+    scheme,
+    char(':'),
+    take_while(|ch: char| !ch.is_ascii_control() && ch != ' ' && ch != '<' && ch != '>'),
+  )))(input)
+}
+
+fn email_label(input: &str) -> IResult<&str, &str> {
+  verify(
+    take_while_m_n(1, 63, |ch: char| ch.is_ascii_alphanumeric() || ch == '-'),
+    |label: &str| !label.starts_with('-') && !label.ends_with('-'),
+  )(input)
+}
+
+fn email_address(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((
+    take_while1(|ch: char| ch.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(ch)),
+    char('@'),
+    email_label,
+    many0(pair(char('.'), email_label)),
+  )))(input)
+}
+
+/// Parse an autolink between angle brackets, e.g. `<https://example.com>` or
+/// `<foo@bar.example.com>`, following the spec's URI and email grammar.
+pub fn autolink(input: &str) -> IResult<&str, Inline> {
+  let (input, link) = delimited(char('<'), alt((absolute_uri, email_address)), char('>'))(input)?;
+  Ok((input, AutoLink(link.to_string())))
+}
+
+fn domain(input: &str) -> IResult<&str, &str> {
+  take_while1(|ch: char| ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == '.')(input)
+}
+
+/// GFM valid domain: at least one period (when `need_period` is set) and no
+/// underscores in the last two segments.
+fn valid_domain(domain: &str, need_period: bool) -> bool {
+  let segments: Vec<&str> = domain.split('.').collect();
+  if need_period && segments.len() < 2 {
+    return false;
+  }
+  !segments.iter().rev().take(2).any(|segment| segment.contains('_'))
+}
+
+/// Length of an extended autolink once trailing punctuation, unmatched closing
+/// parentheses and trailing entity references are removed.
+fn trim_trailing(link: &str) -> usize {
+  let mut end = link.len();
+  loop {
+    let current = &link[..end];
+    match current.chars().last() {
+      Some(ch) if "?!.,:*_~".contains(ch) => end -= 1,
+      Some(')') => {
+        let opening = current.matches('(').count();
+        let closing = current.matches(')').count();
+        if closing > opening {
+          end -= 1;
+        } else {
+          return end;
+        }
+      }
+      Some(';') => {
+        let body = current[..end - 1].trim_end_matches(|ch: char| ch.is_ascii_alphanumeric());
+        if body.ends_with('&') && body.len() < end - 1 {
+          end = body.len() - 1;
+        } else {
+          return end;
+        }
+      }
+      _ => return end,
+    }
+  }
+}
+
+fn www_or_url(input: &str) -> IResult<&str, &str> {
+  let (rest, prefix) = alt((tag_no_case("www."), tag_no_case("https://"), tag_no_case("http://")))(input)?;
+  let www = prefix.eq_ignore_ascii_case("www.");
+  let (rest, host) = domain(rest)?;
+  let host = if www { &input[..prefix.len() + host.len()] } else { host };
+  if !valid_domain(host.trim_end_matches('.'), !www) {
+    return Err(Err::Error(Error::new(input, ErrorKind::Verify)));
+  }
+  let (_, path) = take_while(|ch: char| !ch.is_whitespace() && ch != '<')(rest)?;
+  let end = trim_trailing(&input[..input.len() - rest.len() + path.len()]);
+  if end <= prefix.len() {
+    return Err(Err::Error(Error::new(input, ErrorKind::Verify)));
+  }
+  Ok((&input[end..], &input[..end]))
+}
+
+fn extended_email(input: &str) -> IResult<&str, &str> {
+  let (_, (local, _, host)) = tuple((
+    take_while1(|ch: char| ch.is_ascii_alphanumeric() || ".+-_".contains(ch)),
+    char('@'),
+    domain,
+  ))(input)?;
+  let host = host.trim_end_matches('.');
+  if host.ends_with('-') || host.ends_with('_') || !host.contains('.') {
+    return Err(Err::Error(Error::new(input, ErrorKind::Verify)));
+  }
+  let end = local.len() + 1 + host.len();
+  Ok((&input[end..], &input[..end]))
+}
+
+/// Parse a GFM extended autolink starting at the current position: a bare
+/// `www.` domain, an `http://` or `https://` URL, or an email address.
+///
+/// The caller is responsible for checking what precedes the link, see
+/// [`extended_autolink_boundary`].
+pub fn extended_autolink(input: &str) -> IResult<&str, Inline> {
+  let (input, link) = alt((www_or_url, extended_email))(input)?;
+  Ok((input, AutoLink(link.to_string())))
+}
+
+/// Whether an extended autolink may start right after `prev`.  `www.` and URL
+/// links need the start of a line, whitespace or one of `*`, `_`, `~`, `(`;
+/// an email address only needs to not continue a preceding word.
+pub fn extended_autolink_boundary(prev: Option<char>, input: &str) -> bool {
+  match prev {
+    None => true,
+    Some(ch) if ch.is_whitespace() || "*_~(".contains(ch) => true,
+    Some(ch) => !(ch.is_ascii_alphanumeric() || ".+-_".contains(ch)) && extended_email(input).is_ok(),
+  }
+}
+
+/// The link destination of an `AutoLink`: email addresses get a `mailto:`
+/// scheme and bare `www.` links get `http://`.
+pub fn destination(link: &str) -> String {
+  if link.len() >= 4 && link[..4].eq_ignore_ascii_case("www.") {
+    format!("http://{}", link)
+  } else if absolute_uri(link).is_ok_and(|(rest, _)| rest.is_empty()) {
+    link.to_string()
+  } else {
+    format!("mailto:{}", link)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_commonmark_ex590() {
+    let input = "<http://foo.bar.baz>";
+    let want = AutoLink("http://foo.bar.baz".to_string());
+    let (input, result) = autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex592() {
+    let input = "<irc://foo.bar:2233/baz>";
+    let want = AutoLink("irc://foo.bar:2233/baz".to_string());
+    let (input, result) = autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex594() {
+    let input = "<a+b+c:d>";
+    let want = AutoLink("a+b+c:d".to_string());
+    let (input, result) = autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex597() {
+    let input = "<localhost:5001/foo>";
+    let want = AutoLink("localhost:5001/foo".to_string());
+    let (input, result) = autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+    assert_eq!(destination("localhost:5001/foo"), "localhost:5001/foo");
+  }
+
+  #[test]
+  fn test_commonmark_ex598() {
+    let input = "<http://foo.bar/baz bim>";
+    assert!(autolink(input).is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex600() {
+    let input = "<foo@bar.example.com>";
+    let want = AutoLink("foo@bar.example.com".to_string());
+    let (input, result) = autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+    assert_eq!(destination("foo@bar.example.com"), "mailto:foo@bar.example.com");
+  }
+
+  #[test]
+  fn test_commonmark_ex602() {
+    let input = "<foo\\+@bar.example.com>";
+    assert!(autolink(input).is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex603() {
+    let input = "<>";
+    assert!(autolink(input).is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex604() {
+    let input = "< http://foo.bar >";
+    assert!(autolink(input).is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex605() {
+    let input = "<m:abc>";
+    assert!(autolink(input).is_err());
+  }
+
+  #[test]
+  fn test_gfm_ex621() {
+    let input = "www.commonmark.org";
+    let want = AutoLink("www.commonmark.org".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+    assert_eq!(destination("www.commonmark.org"), "http://www.commonmark.org");
+  }
+
+  #[test]
+  fn test_gfm_ex622() {
+    let input = "www.commonmark.org/help for more information.";
+    let want = AutoLink("www.commonmark.org/help".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, " for more information.");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex623() {
+    let input = "www.commonmark.org.";
+    let want = AutoLink("www.commonmark.org".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, ".");
+    assert_eq!(want, result);
+
+    let input = "www.commonmark.org/a.b.";
+    let want = AutoLink("www.commonmark.org/a.b".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, ".");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex624() {
+    let input = "www.google.com/search?q=Markup+(business))";
+    let want = AutoLink("www.google.com/search?q=Markup+(business)".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, ")");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex625() {
+    let input = "www.google.com/search?q=(business))+ok";
+    let want = AutoLink("www.google.com/search?q=(business))+ok".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex626() {
+    let input = "www.google.com/search?q=commonmark&hl;";
+    let want = AutoLink("www.google.com/search?q=commonmark".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, "&hl;");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex627() {
+    let input = "www.commonmark.org/he<lp";
+    let want = AutoLink("www.commonmark.org/he".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, "<lp");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex628() {
+    let input = "https://encrypted.google.com/search?q=Markup+(business))";
+    let want = AutoLink("https://encrypted.google.com/search?q=Markup+(business)".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, ")");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex629() {
+    let input = "foo@bar.baz";
+    let want = AutoLink("foo@bar.baz".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex630() {
+    assert!(extended_autolink("hello@mail+xyz.example").is_err());
+    assert!(!extended_autolink_boundary(Some('+'), "xyz@mail.example"));
+
+    let input = "hello+xyz@mail.example is.";
+    let want = AutoLink("hello+xyz@mail.example".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, " is.");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex631() {
+    let input = "a.b-c_d@a.b.";
+    let want = AutoLink("a.b-c_d@a.b".to_string());
+    let (input, result) = extended_autolink(input).unwrap();
+    assert_eq!(input, ".");
+    assert_eq!(want, result);
+
+    assert!(extended_autolink("a.b-c_d@a.b-").is_err());
+    assert!(extended_autolink("a.b-c_d@a.b_").is_err());
+  }
+
+  #[test]
+  fn test_extended_autolink_domain() {
+    assert!(extended_autolink("http://commonmark").is_err());
+    assert!(extended_autolink("www.xxx_yyy.zzz.org").is_ok());
+    assert!(extended_autolink("www.xxx.yyy._zzz").is_err());
+    assert!(extended_autolink("www.xxx._yyy.zzz").is_err());
+  }
+
+  #[test]
+  fn test_extended_autolink_boundary() {
+    assert!(extended_autolink_boundary(None, "www.a.b"));
+    assert!(extended_autolink_boundary(Some(' '), "www.a.b"));
+    assert!(extended_autolink_boundary(Some('('), "www.a.b"));
+    assert!(!extended_autolink_boundary(Some('x'), "www.a.b"));
+    assert!(extended_autolink_boundary(Some(','), "foo@bar.baz"));
+  }
+}
@@ -0,0 +1,107 @@
+use nom::{
+  bytes::complete::take_while1,
+  error::{Error, ErrorKind},
+  Err, IResult,
+};
+use crate::types::{Attributes, Inline, Inline::Code};
+
+fn backticks(input: &str) -> IResult<&str, &str> {
+  take_while1(|ch| ch == '`')(input)
+}
+
+/// Line endings become spaces, and one space is stripped from both ends when
+/// the content starts and ends with a space but isn't only spaces.
+fn normalize(content: &str) -> String {
+  let content = content.replace('\n', " ");
+  if content.len() >= 2 && content.starts_with(' ') && content.ends_with(' ') && !content.chars().all(|ch| ch == ' ') {
+    content[1..content.len() - 1].to_string()
+  } else {
+    content
+  }
+}
+
+/// Parse a code span: a backtick string, the content, and a backtick string
+/// of the same length.
+pub fn code_span(input: &str) -> IResult<&str, Inline> {
+  let (rest, opening) = backticks(input)?;
+  let mut index = 0;
+  while let Some(offset) = rest[index..].find('`') {
+    let start = index + offset;
+    let (remaining, closing) = backticks(&rest[start..])?;
+    if closing.len() == opening.len() {
+      return Ok((remaining, Code(normalize(&rest[..start]), Attributes::default())));
+    }
+    index = start + closing.len();
+  }
+  Err(Err::Error(Error::new(input, ErrorKind::TakeUntil)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn code(content: &str) -> Inline {
+    Code(content.to_string(), Attributes::default())
+  }
+
+  #[test]
+  fn test_commonmark_ex328() {
+    assert_eq!(code_span("`foo`"), Ok(("", code("foo"))));
+  }
+
+  #[test]
+  fn test_commonmark_ex329() {
+    assert_eq!(code_span("`` foo ` bar ``"), Ok(("", code("foo ` bar"))));
+  }
+
+  #[test]
+  fn test_commonmark_ex330() {
+    assert_eq!(code_span("` `` `"), Ok(("", code("``"))));
+  }
+
+  #[test]
+  fn test_commonmark_ex331() {
+    assert_eq!(code_span("`  ``  `"), Ok(("", code(" `` "))));
+  }
+
+  #[test]
+  fn test_commonmark_ex332() {
+    assert_eq!(code_span("` a`"), Ok(("", code(" a"))));
+  }
+
+  #[test]
+  fn test_commonmark_ex334() {
+    assert_eq!(code_span("`\u{a0}`"), Ok(("", code("\u{a0}"))));
+    assert_eq!(code_span("`  `"), Ok(("", code("  "))));
+  }
+
+  #[test]
+  fn test_commonmark_ex335() {
+    let input = "``
+foo
+bar  
+baz
+``";
+    assert_eq!(code_span(input), Ok(("", code("foo bar   baz"))));
+  }
+
+  #[test]
+  fn test_commonmark_ex338() {
+    assert_eq!(code_span("`foo\\`bar`"), Ok(("bar`", code("foo\\"))));
+  }
+
+  #[test]
+  fn test_commonmark_ex339() {
+    assert_eq!(code_span("``foo`bar``"), Ok(("", code("foo`bar"))));
+  }
+
+  #[test]
+  fn test_commonmark_ex347() {
+    assert!(code_span("```foo``").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex349() {
+    assert!(code_span("`foo``bar``").is_err());
+  }
+}
@@ -0,0 +1,350 @@
+use nom::{
+  branch::alt,
+  error::{Error, ErrorKind},
+  Err, IResult,
+};
+use std::collections::HashMap;
+use crate::options::Options;
+use crate::types::{Inline, Inline::{Image, Link, Text}};
+use super::{emphasis_or_strong, formula, highlight, link::link_tail, strikethrough, text};
+
+/// A run of delimiter characters, e.g. the `**` in `**foo**`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+  pub ch: char,
+  pub length: usize,
+  pub original: usize,
+  pub can_open: bool,
+  pub can_close: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+  Node(Inline),
+  Run(Run),
+  /// A `[` or `![` that may open a link or an image.  Openers of links get
+  /// deactivated once a link is formed, as links can't contain links.
+  Bracket { image: bool, active: bool },
+}
+
+/// A piece of the inline sequence together with the byte range of the input
+/// it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Spanned {
+  piece: Piece,
+  start: usize,
+  end: usize,
+}
+
+pub fn is_delimiter(ch: char) -> bool {
+  ch == '*' || ch == '_' || ch == '~' || ch == '='
+}
+
+pub fn is_punctuation(ch: char) -> bool {
+  ch.is_ascii_punctuation() || !(ch.is_alphanumeric() || ch.is_whitespace() || ch.is_control())
+}
+
+fn is_whitespace(ch: Option<char>) -> bool {
+  ch.is_none_or(char::is_whitespace)
+}
+
+/// A left-flanking run is not followed by whitespace, and either not followed
+/// by punctuation or preceded by whitespace or punctuation.  `None` stands for
+/// the start or the end of the input.
+pub fn left_flanking(prev: Option<char>, next: Option<char>) -> bool {
+  !is_whitespace(next)
+    && (!next.is_some_and(is_punctuation) || is_whitespace(prev) || prev.is_some_and(is_punctuation))
+}
+
+pub fn right_flanking(prev: Option<char>, next: Option<char>) -> bool {
+  !is_whitespace(prev)
+    && (!prev.is_some_and(is_punctuation) || is_whitespace(next) || next.is_some_and(is_punctuation))
+}
+
+fn run(options: &Options, ch: char, length: usize, prev: Option<char>, next: Option<char>) -> Run {
+  match ch {
+    '~' => strikethrough::run(options, length, prev, next),
+    '=' => highlight::run(options, length, prev, next),
+    _ => emphasis_or_strong::run(ch, length, prev, next),
+  }
+}
+
+fn can_match(opener: &Run, closer: &Run) -> bool {
+  opener.ch == closer.ch
+    && match closer.ch {
+      '~' => strikethrough::can_match(opener, closer),
+      '=' => highlight::can_match(opener, closer),
+      _ => emphasis_or_strong::can_match(opener, closer),
+    }
+}
+
+fn used(opener: &Run, closer: &Run) -> usize {
+  match closer.ch {
+    '~' => strikethrough::used(opener, closer),
+    '=' => highlight::used(opener, closer),
+    _ => emphasis_or_strong::used(opener, closer),
+  }
+}
+
+fn wrap(ch: char, used: usize, children: Vec<Inline>) -> Inline {
+  match ch {
+    '~' => strikethrough::wrap(used, children),
+    '=' => highlight::wrap(used, children),
+    _ => emphasis_or_strong::wrap(used, children),
+  }
+}
+
+/// Try to close the nearest bracket opener with the `]` at `index`.  On
+/// success the opener and everything after it become a link or an image, and
+/// the end of the link is returned.
+fn close_bracket(options: &Options, pieces: &mut Vec<Spanned>, input: &str, index: usize) -> Option<usize> {
+  let opener = pieces.iter().rposition(|spanned| matches!(spanned.piece, Piece::Bracket { .. }))?;
+  let (image, active) = match pieces[opener].piece {
+    Piece::Bracket { image, active } => (image, active),
+    _ => unreachable!(),
+  };
+  let tail = if active { link_tail(&input[index + 1..]).ok() } else { None };
+  let (remaining, (destination, title)) = match tail {
+    Some(tail) => tail,
+    None => {
+      let text = if image { "![" } else { "[" };
+      pieces[opener].piece = Piece::Node(Text(text.to_string()));
+      return None;
+    }
+  };
+  let (remaining, attributes) = super::trailing_attributes(options, remaining);
+  let inner: Vec<Spanned> = pieces.drain(opener + 1..).collect();
+  let children = nodes(process(inner));
+  let start = pieces.pop()?.start;
+  let end = input.len() - remaining.len();
+  let node = if image {
+    Image(children, destination, title, attributes)
+  } else {
+    for spanned in pieces.iter_mut() {
+      if let Piece::Bracket { image: false, active } = &mut spanned.piece {
+        *active = false;
+      }
+    }
+    Link(children, destination, title, attributes)
+  };
+  pieces.push(Spanned { piece: Piece::Node(node), start, end });
+  Some(end)
+}
+
+fn tokenize(options: &Options, prev: Option<char>, input: &str) -> Vec<Spanned> {
+  let mut pieces = vec![];
+  let mut prev = prev;
+  let mut index = 0;
+  // Set once a `$` finds no closing `$`, so that the input isn't scanned
+  // again for every later one.
+  let mut no_closing = false;
+  while let Some(ch) = input[index..].chars().next() {
+    let rest = &input[index..];
+    if options.math && ch == '$' {
+      // A run of several `$` is text, so that `$$x$$` doesn't read as a
+      // formula between two stray `$`.
+      let length = rest.chars().take_while(|&c| c == '$').count();
+      if length == 1 && !no_closing && formula::can_open(&rest[1..]) {
+        no_closing = formula::closing(&rest[1..]).is_none();
+      }
+      if length > 1 || no_closing {
+        pieces.push(Spanned { piece: Piece::Node(Text("$".repeat(length))), start: index, end: index + length });
+        prev = Some('$');
+        index += length;
+        continue;
+      }
+    }
+    if is_delimiter(ch) && super::script(options, rest).is_err() {
+      let length = rest.chars().take_while(|&c| c == ch).count();
+      let next = rest[length..].chars().next();
+      pieces.push(Spanned { piece: Piece::Run(run(options, ch, length, prev, next)), start: index, end: index + length });
+      prev = Some(ch);
+      index += length;
+      continue;
+    }
+    if (ch == '[' || rest.starts_with("![")) && super::atom(options, prev, rest).is_err() {
+      let image = ch == '!';
+      let end = index + if image { 2 } else { 1 };
+      pieces.push(Spanned { piece: Piece::Bracket { image, active: true }, start: index, end });
+      prev = Some('[');
+      index = end;
+      continue;
+    }
+    if ch == ']' {
+      if let Some(end) = close_bracket(options, &mut pieces, input, index) {
+        prev = Some(')');
+        index = end;
+        continue;
+      }
+    }
+    let node = alt((
+      |rest| super::script(options, rest),
+      |rest| super::atom(options, prev, rest),
+      text::text,
+      text::literal,
+    ))(rest);
+    let (remaining, node) = match node {
+      Ok(node) => node,
+      Err(_) => break,
+    };
+    let end = input.len() - remaining.len();
+    pieces.push(Spanned { piece: Piece::Node(node), start: index, end });
+    prev = input[..end].chars().last();
+    index = end;
+  }
+  pieces
+}
+
+fn nodes(pieces: Vec<Spanned>) -> Vec<Inline> {
+  pieces
+    .into_iter()
+    .filter_map(|spanned| match spanned.piece {
+      Piece::Node(node) => Some(node),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Turn the remaining delimiter runs and brackets into text and merge
+/// adjacent texts.
+fn finish(pieces: Vec<Spanned>) -> Vec<Spanned> {
+  let mut result: Vec<Spanned> = vec![];
+  for spanned in pieces {
+    let spanned = match spanned.piece {
+      Piece::Run(run) if run.length == 0 => continue,
+      Piece::Run(run) => Spanned { piece: Piece::Node(Text(run.ch.to_string().repeat(run.length))), ..spanned },
+      Piece::Bracket { image, .. } => {
+        let text = if image { "![" } else { "[" };
+        Spanned { piece: Piece::Node(Text(text.to_string())), ..spanned }
+      }
+      Piece::Node(_) => spanned,
+    };
+    match (result.last_mut(), &spanned.piece) {
+      (Some(Spanned { piece: Piece::Node(Text(last)), end, .. }), Piece::Node(Text(text))) => {
+        last.push_str(text);
+        *end = spanned.end;
+      }
+      _ => result.push(spanned),
+    }
+  }
+  result
+}
+
+/// The spec's "process emphasis" procedure: walk the closers from left to
+/// right, look back for the nearest matching opener and wrap everything in
+/// between.
+///
+/// As in the spec, the search is bounded by `openers_bottom`: once a closer
+/// finds no opener, later closers of the same character, length modulo 3 and
+/// ability to open can't find one below it either.  This keeps inputs with
+/// many unmatched runs linear.
+fn process(mut pieces: Vec<Spanned>) -> Vec<Spanned> {
+  let mut openers_bottom: HashMap<(char, bool, usize), usize> = HashMap::new();
+  let mut closer_index = 0;
+  while closer_index < pieces.len() {
+    let closer = match pieces[closer_index].piece {
+      Piece::Run(run) if run.can_close && run.length > 0 => run,
+      _ => {
+        closer_index += 1;
+        continue;
+      }
+    };
+    let key = (closer.ch, closer.can_open, closer.original % 3);
+    let bottom = openers_bottom.get(&key).copied().unwrap_or(0);
+    let opener_index = (bottom..closer_index).rev().find(|&index| match pieces[index].piece {
+      Piece::Run(run) => run.can_open && run.length > 0 && can_match(&run, &closer),
+      _ => false,
+    });
+    let opener_index = match opener_index {
+      Some(index) => index,
+      None => {
+        openers_bottom.insert(key, closer_index);
+        closer_index += 1;
+        continue;
+      }
+    };
+    // The pieces between the opener and the closer are about to be replaced
+    // by a single node, so no bottom may point past the opener.
+    for bottom in openers_bottom.values_mut() {
+      *bottom = (*bottom).min(opener_index + 1);
+    }
+    let opener = match pieces[opener_index].piece {
+      Piece::Run(run) => run,
+      _ => unreachable!(),
+    };
+    let used = used(&opener, &closer);
+    let inner: Vec<Spanned> = pieces.drain(opener_index + 1..closer_index).collect();
+    let children = finish(inner).into_iter().map(|spanned| match spanned.piece {
+      Piece::Node(node) => node,
+      _ => unreachable!(),
+    });
+
+    let mut index = opener_index + 1;
+    pieces[opener_index].end -= used;
+    pieces[index].start += used;
+    let start = pieces[opener_index].end;
+    let end = pieces[index].start;
+    for piece in [opener_index, index] {
+      if let Piece::Run(run) = &mut pieces[piece].piece {
+        run.length -= used;
+      }
+    }
+    let node = wrap(closer.ch, used, children.collect());
+    pieces.insert(index, Spanned { piece: Piece::Node(node), start, end });
+    index += 1;
+    if opener.length == used {
+      pieces.remove(opener_index);
+      index -= 1;
+    }
+    if closer.length == used {
+      pieces.remove(index);
+    }
+    closer_index = index;
+  }
+  finish(pieces)
+}
+
+/// Parse the inline sequence in `input`, resolving delimiter runs into
+/// emphasis, strong emphasis and strikethrough, and brackets into links and
+/// images.  `prev` is the character right before `input`, if any.
+pub fn resolve(options: &Options, prev: Option<char>, input: &str) -> Vec<Inline> {
+  nodes(process(tokenize(options, prev, input)))
+}
+
+/// Parse the node opened by the delimiter run or bracket at the start of
+/// `input`, if it gets closed.
+pub fn delimited(options: &Options, prev: Option<char>) -> impl Fn(&str) -> IResult<&str, Inline> + '_ {
+  move |input: &str| {
+    if !(input.starts_with(is_delimiter) || input.starts_with('[') || input.starts_with("![")) {
+      return Err(Err::Error(Error::new(input, ErrorKind::Char)));
+    }
+    match process(tokenize(options, prev, input)).into_iter().next() {
+      Some(Spanned { piece: Piece::Node(node), end, .. }) if !matches!(node, Text(_)) => Ok((&input[end..], node)),
+      _ => Err(Err::Error(Error::new(input, ErrorKind::Verify))),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_unmatched_closers_stay_linear() {
+    // Every `_` closer would otherwise look back through all of the `*`
+    // openers before giving up.
+    let input = format!("{}{}", "*a ".repeat(10000), "b_ ".repeat(10000));
+    assert_eq!(resolve(&Options::default(), None, &input), vec![Text(input.clone())]);
+  }
+
+  #[test]
+  fn test_openers_bottom_after_match() {
+    let input = "_a *b_ c* *d*";
+    let want = vec![
+      Inline::Emphasis(vec![Text("a *b".to_string())]),
+      Text(" c* ".to_string()),
+      Inline::Emphasis(vec![Text("d".to_string())]),
+    ];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+  }
+}
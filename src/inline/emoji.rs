@@ -0,0 +1,47 @@
+use nom::{
+  bytes::complete::take_while1,
+  character::complete::char,
+  combinator::map_opt,
+  sequence::delimited,
+  IResult,
+};
+use crate::types::{Inline, Inline::Emoji};
+use super::emojis::emoji as lookup;
+
+/// Parse a GitHub emoji shortcode such as `:tada:` or `:+1:`.  Shortcodes
+/// that aren't in the table are not emoji.
+pub fn emoji(input: &str) -> IResult<&str, Inline> {
+  map_opt(
+    delimited(
+      char(':'),
+      take_while1(|ch: char| ch.is_ascii_lowercase() || ch.is_ascii_digit() || "_+-".contains(ch)),
+      char(':'),
+    ),
+    |shortcode: &str| lookup(shortcode).map(|emoji| Emoji(shortcode.to_string(), emoji.to_string())),
+  )(input)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_emoji() {
+    let (input, result) = emoji(":tada: done").unwrap();
+    assert_eq!(input, " done");
+    assert_eq!(Emoji("tada".to_string(), "🎉".to_string()), result);
+  }
+
+  #[test]
+  fn test_emoji_punctuation_in_shortcode() {
+    assert_eq!(emoji(":+1:"), Ok(("", Emoji("+1".to_string(), "👍".to_string()))));
+    assert_eq!(emoji(":-1:"), Ok(("", Emoji("-1".to_string(), "👎".to_string()))));
+  }
+
+  #[test]
+  fn test_emoji_unknown_shortcode() {
+    assert!(emoji(":not_an_emoji:").is_err());
+    assert!(emoji(":Tada:").is_err());
+    assert!(emoji("::").is_err());
+  }
+}
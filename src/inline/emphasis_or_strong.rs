@@ -1,7 +1,184 @@
-#![allow(dead_code)]
-use nom::{IResult, bytes::complete::take_while};
-use crate::types::{Inline, Inline::{Text, Emphasis}};
+use crate::types::{Inline, Inline::{Emphasis, Strong}};
+use super::delimiter::{is_punctuation, left_flanking, right_flanking, Run};
 
-pub fn emphasis_or_strong(input: &str) -> IResult<&str, Inline> {
-  todo!()
-}
\ No newline at end of file
+/// A `*` run opens when left-flanking and closes when right-flanking.  A `_`
+/// run additionally must not open or close in the middle of a word.
+pub fn run(ch: char, length: usize, prev: Option<char>, next: Option<char>) -> Run {
+  let left = left_flanking(prev, next);
+  let right = right_flanking(prev, next);
+  let (can_open, can_close) = if ch == '_' {
+    (
+      left && (!right || prev.is_some_and(is_punctuation)),
+      right && (!left || next.is_some_and(is_punctuation)),
+    )
+  } else {
+    (left, right)
+  };
+  Run { ch, length, original: length, can_open, can_close }
+}
+
+/// The "rule of 3": if one of the runs can both open and close, the sum of
+/// their lengths must not be a multiple of 3 unless both lengths are.
+pub fn can_match(opener: &Run, closer: &Run) -> bool {
+  !((opener.can_close || closer.can_open)
+    && (opener.original + closer.original).is_multiple_of(3)
+    && !(opener.original.is_multiple_of(3) && closer.original.is_multiple_of(3)))
+}
+
+pub fn used(opener: &Run, closer: &Run) -> usize {
+  if opener.length >= 2 && closer.length >= 2 {
+    2
+  } else {
+    1
+  }
+}
+
+pub fn wrap(used: usize, children: Vec<Inline>) -> Inline {
+  if used == 2 {
+    Strong(children)
+  } else {
+    Emphasis(children)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::inline::delimiter::{delimited, resolve};
+  use crate::options::Options;
+  use crate::types::Inline::{AutoLink, RawHTML, Text};
+
+  fn text(content: &str) -> Inline {
+    Text(content.to_string())
+  }
+
+  #[test]
+  fn test_commonmark_ex352() {
+    let input = "a*\"foo\"*";
+    assert_eq!(resolve(&Options::default(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_commonmark_ex354() {
+    let input = "foo*bar*";
+    let want = vec![text("foo"), Emphasis(vec![text("bar")])];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex359() {
+    let input = "foo_bar_";
+    assert_eq!(resolve(&Options::default(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_commonmark_ex365() {
+    let input = "*foo bar *";
+    assert!(delimited(&Options::default(), None)(input).is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex375() {
+    let input = "_foo_bar_baz_";
+    let want = Emphasis(vec![text("foo_bar_baz")]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex377() {
+    let input = "**foo bar**";
+    let want = Strong(vec![text("foo bar")]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex388() {
+    let input = "__foo, __bar__, baz__";
+    let want = Strong(vec![text("foo, "), Strong(vec![text("bar")]), text(", baz")]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex407() {
+    let input = "__foo_ bar_";
+    let want = Emphasis(vec![Emphasis(vec![text("foo")]), text(" bar")]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex411() {
+    let input = "*foo**bar*";
+    let want = Emphasis(vec![text("foo**bar")]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex412() {
+    let input = "***foo** bar*";
+    let want = Emphasis(vec![Strong(vec![text("foo")]), text(" bar")]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex416() {
+    let input = "foo******bar*********baz";
+    let want = vec![
+      text("foo"),
+      Strong(vec![Strong(vec![Strong(vec![text("bar")])])]),
+      text("***baz"),
+    ];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex444() {
+    let input = "****foo*";
+    let want = vec![text("***"), Emphasis(vec![text("foo")])];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+    assert!(delimited(&Options::default(), None)(input).is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex467() {
+    let input = "_____foo_____";
+    let want = Emphasis(vec![Strong(vec![Strong(vec![text("foo")])])]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex468() {
+    let input = "*foo _bar* baz_";
+    let want = Emphasis(vec![text("foo _bar")]);
+    let (input, result) = delimited(&Options::default(), None)(input).unwrap();
+    assert_eq!(input, " baz_");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex475() {
+    let input = "**<a href=\"**\">";
+    let want = vec![text("**"), RawHTML("<a href=\"**\">".to_string())];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex479() {
+    let input = "**a<http://foo.bar/?q=**>";
+    let want = vec![text("**a"), AutoLink("http://foo.bar/?q=**".to_string())];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+  }
+}
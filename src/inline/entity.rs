@@ -0,0 +1,102 @@
+use nom::{
+  branch::alt,
+  bytes::complete::{tag_no_case, take_while_m_n},
+  character::complete::{char, satisfy},
+  combinator::{map_opt, recognize},
+  sequence::{delimited, pair, preceded},
+  IResult,
+};
+use crate::types::{Inline, Inline::Text};
+use super::entities::entity;
+
+fn codepoint(value: u32) -> String {
+  match std::char::from_u32(value) {
+    Some(ch) if value != 0 => ch.to_string(),
+    _ => '\u{FFFD}'.to_string(),
+  }
+}
+
+fn named(input: &str) -> IResult<&str, String> {
+  map_opt(
+    recognize(pair(
+      satisfy(|ch| ch.is_ascii_alphabetic()),
+      take_while_m_n(1, 31, |ch: char| ch.is_ascii_alphanumeric()),
+    )),
+    |name: &str| entity(name).map(str::to_string),
+  )(input)
+}
+
+fn decimal(input: &str) -> IResult<&str, String> {
+  map_opt(
+    preceded(char('#'), take_while_m_n(1, 7, |ch: char| ch.is_ascii_digit())),
+    |digits: &str| digits.parse().ok().map(codepoint),
+  )(input)
+}
+
+fn hexadecimal(input: &str) -> IResult<&str, String> {
+  map_opt(
+    preceded(tag_no_case("#x"), take_while_m_n(1, 6, |ch: char| ch.is_ascii_hexdigit())),
+    |digits: &str| u32::from_str_radix(digits, 16).ok().map(codepoint),
+  )(input)
+}
+
+/// Parse an entity or numeric character reference such as `&amp;`, `&#35;`
+/// or `&#x22;` into the string it stands for.  Invalid code points become
+/// U+FFFD.
+pub fn decode(input: &str) -> IResult<&str, String> {
+  delimited(char('&'), alt((named, decimal, hexadecimal)), char(';'))(input)
+}
+
+pub fn entity_reference(input: &str) -> IResult<&str, Inline> {
+  let (input, decoded) = decode(input)?;
+  Ok((input, Text(decoded)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_commonmark_ex311() {
+    for (input, want) in [("&nbsp;", "\u{a0}"), ("&copy;", "©"), ("&HilbertSpace;", "ℋ"), ("&ngE;", "≧̸")] {
+      let (input, result) = entity_reference(input).unwrap();
+      assert_eq!(input, "");
+      assert_eq!(Text(want.to_string()), result);
+    }
+  }
+
+  #[test]
+  fn test_commonmark_ex312() {
+    for (input, want) in [("&#35;", "#"), ("&#1234;", "Ӓ"), ("&#992;", "Ϡ"), ("&#0;", "\u{FFFD}")] {
+      let (input, result) = entity_reference(input).unwrap();
+      assert_eq!(input, "");
+      assert_eq!(Text(want.to_string()), result);
+    }
+  }
+
+  #[test]
+  fn test_commonmark_ex313() {
+    for (input, want) in [("&#X22;", "\""), ("&#XD06;", "ആ"), ("&#xcab;", "ಫ")] {
+      let (input, result) = entity_reference(input).unwrap();
+      assert_eq!(input, "");
+      assert_eq!(Text(want.to_string()), result);
+    }
+  }
+
+  #[test]
+  fn test_commonmark_ex314() {
+    for input in ["&nbsp", "&x;", "&#;", "&#x;", "&#87654321;", "&#abcdef0;", "&ThisIsNotDefined;", "&hi?;"] {
+      assert!(entity_reference(input).is_err());
+    }
+  }
+
+  #[test]
+  fn test_commonmark_ex315() {
+    assert!(entity_reference("&copy").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex316() {
+    assert!(entity_reference("&MadeUpEntity;").is_err());
+  }
+}
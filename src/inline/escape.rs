@@ -0,0 +1,61 @@
+use nom::{
+  character::complete::{char, satisfy},
+  sequence::preceded,
+  IResult,
+};
+use crate::types::{Inline, Inline::Literal};
+use super::entity::decode;
+
+/// A backslash followed by an ASCII punctuation character stands for that
+/// character literally.
+pub fn backslash_escape(input: &str) -> IResult<&str, Inline> {
+  let (input, ch) = preceded(char('\\'), satisfy(|ch| ch.is_ascii_punctuation()))(input)?;
+  Ok((input, Literal(ch)))
+}
+
+/// Resolve backslash escapes and entity references in link destinations,
+/// titles and code block info strings.
+pub fn unescape(input: &str) -> String {
+  let mut result = String::new();
+  let mut rest = input;
+  while let Some(ch) = rest.chars().next() {
+    if let Ok((remaining, Literal(escaped))) = backslash_escape(rest) {
+      result.push(escaped);
+      rest = remaining;
+    } else if let Ok((remaining, decoded)) = decode(rest) {
+      result.push_str(&decoded);
+      rest = remaining;
+    } else {
+      result.push(ch);
+      rest = &rest[ch.len_utf8()..];
+    }
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_commonmark_ex298() {
+    for want in "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~".chars() {
+      let escaped = format!("\\{}", want);
+      let (remaining, result) = backslash_escape(&escaped).unwrap();
+      assert_eq!(remaining, "");
+      assert_eq!(Literal(want), result);
+    }
+  }
+
+  #[test]
+  fn test_commonmark_ex299() {
+    for input in ["\\\t", "\\A", "\\a", "\\ ", "\\3", "\\φ", "\\«"] {
+      assert!(backslash_escape(input).is_err());
+    }
+  }
+
+  #[test]
+  fn test_unescape() {
+    assert_eq!(unescape("/bar\\* \\a &ouml;&#35;&nope"), "/bar* \\a ö#&nope");
+  }
+}
@@ -0,0 +1,39 @@
+use nom::{
+  bytes::complete::tag,
+  character::complete::char,
+  sequence::delimited,
+  IResult,
+};
+use crate::types::{Inline, Inline::FootnoteReference};
+use super::link::{label, normalize_label};
+
+/// `[^label]`, giving the normalized label.
+pub fn footnote_label(input: &str) -> IResult<&str, String> {
+  let (input, label) = delimited(tag("[^"), label, char(']'))(input)?;
+  Ok((input, normalize_label(label)))
+}
+
+/// A footnote reference such as `[^1]`.  Whether the footnote is defined is
+/// only known once the whole document is parsed.
+pub fn footnote_reference(input: &str) -> IResult<&str, Inline> {
+  let (input, label) = footnote_label(input)?;
+  Ok((input, FootnoteReference(label)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_footnote_reference() {
+    let (input, result) = footnote_reference("[^Note  One] and").unwrap();
+    assert_eq!(input, " and");
+    assert_eq!(FootnoteReference("note one".to_string()), result);
+  }
+
+  #[test]
+  fn test_footnote_reference_empty() {
+    assert!(footnote_reference("[^]").is_err());
+    assert!(footnote_reference("[^ ]").is_err());
+  }
+}
@@ -0,0 +1,118 @@
+use nom::{
+  character::complete::{char, none_of},
+  combinator::peek,
+  error::{Error, ErrorKind},
+  Err, IResult,
+};
+use crate::types::{Inline, Inline::Formula};
+
+/// Parse inline math between single dollar signs, e.g. `$e^{i\pi} = -1$`.
+///
+/// The opening `$` must not be followed by whitespace or another `$`, the
+/// closing `$` must not be preceded by whitespace nor followed by a digit, so
+/// that `$5 and $10` stays text.  A backslash escapes the next character, so
+/// `\$` never closes the formula.  The content is kept verbatim.
+pub fn formula(input: &str) -> IResult<&str, Inline> {
+  let (rest, _) = char('$')(input)?;
+  peek(none_of(" \t\n$"))(rest)?;
+  match closing(rest) {
+    Some(index) => Ok((&rest[index + 1..], Formula(rest[..index].to_string()))),
+    None => Err(Err::Error(Error::new(input, ErrorKind::Verify))),
+  }
+}
+
+/// Whether a `$` right before `rest` may open a formula.
+pub fn can_open(rest: &str) -> bool {
+  peek::<_, _, Error<&str>, _>(none_of(" \t\n$"))(rest).is_ok()
+}
+
+/// The index in `rest`, the input after an opening `$`, of the `$` closing
+/// the formula.  Whether a `$` closes doesn't depend on the opener, so when
+/// there is none, no later opener has one either.
+pub fn closing(rest: &str) -> Option<usize> {
+  let mut chars = rest.char_indices();
+  let mut prev = None;
+  while let Some((index, ch)) = chars.next() {
+    match ch {
+      '\\' => {
+        chars.next();
+      }
+      '$'
+        if !prev.is_some_and(char::is_whitespace)
+          && !rest[index + 1..].starts_with(|next: char| next.is_ascii_digit()) =>
+      {
+        return Some(index);
+      }
+      _ => {}
+    }
+    prev = Some(ch);
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_formula() {
+    let input = "$x^2 + y^2 = z^2$ holds";
+    let want = Formula("x^2 + y^2 = z^2".to_string());
+    let (input, result) = formula(input).unwrap();
+    assert_eq!(input, " holds");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_formula_verbatim() {
+    let input = "$a *b* `c` \\alpha$";
+    let want = Formula("a *b* `c` \\alpha".to_string());
+    let (input, result) = formula(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_formula_space_after_opening() {
+    assert!(formula("$ x$").is_err());
+  }
+
+  #[test]
+  fn test_formula_space_before_closing() {
+    assert!(formula("$x $").is_err());
+  }
+
+  #[test]
+  fn test_formula_digit_after_closing() {
+    assert!(formula("$5 and $10").is_err());
+    assert!(formula("$5$10").is_err());
+  }
+
+  #[test]
+  fn test_formula_skips_invalid_closing() {
+    let input = "$a $b$";
+    let want = Formula("a $b".to_string());
+    let (input, result) = formula(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_formula_escaped_dollar() {
+    let input = "$\\$5$";
+    let want = Formula("\\$5".to_string());
+    let (input, result) = formula(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_formula_display_delimiters() {
+    assert!(formula("$$x$$").is_err());
+  }
+
+  #[test]
+  fn test_formula_unclosed() {
+    assert!(formula("$.;'there").is_err());
+  }
+}
@@ -0,0 +1,95 @@
+use crate::options::Options;
+use crate::types::{Inline, Inline::Highlight};
+use super::delimiter::{left_flanking, right_flanking, Run};
+
+/// Only runs of exactly two `=` delimit highlighting, and they follow the same
+/// flanking rules as `*`, so `a == b` stays text.
+pub fn run(options: &Options, length: usize, prev: Option<char>, next: Option<char>) -> Run {
+  let delimits = options.highlight && length == 2;
+  Run {
+    ch: '=',
+    length,
+    original: length,
+    can_open: delimits && left_flanking(prev, next),
+    can_close: delimits && right_flanking(prev, next),
+  }
+}
+
+pub fn can_match(opener: &Run, closer: &Run) -> bool {
+  opener.length == closer.length
+}
+
+pub fn used(_opener: &Run, closer: &Run) -> usize {
+  closer.length
+}
+
+pub fn wrap(_used: usize, children: Vec<Inline>) -> Inline {
+  Highlight(children)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::inline::delimiter::{delimited, resolve};
+  use nom::{
+    error::{Error, ErrorKind},
+    Err, IResult,
+  };
+  use crate::types::Inline::{Emphasis, Text};
+
+  fn text(content: &str) -> Inline {
+    Text(content.to_string())
+  }
+
+  fn highlight<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Inline> {
+    if !input.starts_with('=') {
+      return Err(Err::Error(Error::new(input, ErrorKind::Char)));
+    }
+    delimited(options, None)(input)
+  }
+
+  fn options() -> Options {
+    Options {
+      highlight: true,
+      ..Options::default()
+    }
+  }
+
+  #[test]
+  fn test_highlight() {
+    let input = "==marked== text";
+    let want = Highlight(vec![text("marked")]);
+    let (input, result) = highlight(&options(), input).unwrap();
+    assert_eq!(input, " text");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_highlight_disabled_by_default() {
+    let input = "==marked==";
+    assert_eq!(resolve(&Options::default(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_highlight_comparison() {
+    let input = "if a == b and c == d";
+    assert_eq!(resolve(&options(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_highlight_run_length() {
+    let input = "=single= and ===triple===";
+    assert_eq!(resolve(&options(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_highlight_with_emphasis() {
+    let input = "*a ==b==* ==*c*==";
+    let want = vec![
+      Emphasis(vec![text("a "), Highlight(vec![text("b")])]),
+      text(" "),
+      Highlight(vec![Emphasis(vec![text("c")])]),
+    ];
+    assert_eq!(resolve(&options(), None, input), want);
+  }
+}
@@ -0,0 +1,84 @@
+use nom::{
+  branch::alt,
+  bytes::complete::{tag, take_while},
+  character::complete::char,
+  combinator::{not, eof},
+  sequence::{terminated, tuple},
+  IResult,
+};
+use crate::types::{Inline, Inline::{HardBreak, SoftBreak}};
+
+fn spaces(input: &str) -> IResult<&str, &str> {
+  take_while(|ch| ch == ' ')(input)
+}
+
+/// Parse a line ending inside a paragraph together with the spaces around it.
+/// Two or more trailing spaces, or a backslash right before the newline, make
+/// a hard break; anything else is a soft break.  Neither is recognized at the
+/// end of the input, since breaks only separate inline content.
+pub fn line_break(input: &str) -> IResult<&str, Inline> {
+  let (input, (trailing, _, _)) = terminated(
+    tuple((alt((tag("\\"), spaces)), char('\n'), spaces)),
+    not(eof),
+  )(input)?;
+  if trailing == "\\" || trailing.len() >= 2 {
+    Ok((input, HardBreak))
+  } else {
+    Ok((input, SoftBreak))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_commonmark_ex630() {
+    let input = "  \nbaz";
+    let (input, result) = line_break(input).unwrap();
+    assert_eq!(input, "baz");
+    assert_eq!(HardBreak, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex631() {
+    let input = "\\\nbaz";
+    let (input, result) = line_break(input).unwrap();
+    assert_eq!(input, "baz");
+    assert_eq!(HardBreak, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex633() {
+    let input = "  \n     bar";
+    let (input, result) = line_break(input).unwrap();
+    assert_eq!(input, "bar");
+    assert_eq!(HardBreak, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex641() {
+    assert!(line_break("\\\n").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex642() {
+    assert!(line_break("  \n").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex645() {
+    let input = "\nbaz";
+    let (input, result) = line_break(input).unwrap();
+    assert_eq!(input, "baz");
+    assert_eq!(SoftBreak, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex646() {
+    let input = " \n baz";
+    let (input, result) = line_break(input).unwrap();
+    assert_eq!(input, "baz");
+    assert_eq!(SoftBreak, result);
+  }
+}
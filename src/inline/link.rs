@@ -0,0 +1,208 @@
+use nom::{
+  branch::alt,
+  bytes::complete::take_while,
+  character::complete::char,
+  combinator::opt,
+  error::{Error, ErrorKind},
+  sequence::{delimited, pair, preceded, terminated},
+  Err, IResult,
+};
+use super::escape::unescape;
+
+fn whitespace(input: &str) -> IResult<&str, &str> {
+  take_while(|ch| ch == ' ' || ch == '\t' || ch == '\n')(input)
+}
+
+fn whitespace1(input: &str) -> IResult<&str, &str> {
+  let (rest, spaces) = whitespace(input)?;
+  if spaces.is_empty() {
+    return Err(Err::Error(Error::new(input, ErrorKind::Space)));
+  }
+  Ok((rest, spaces))
+}
+
+/// Scan up to the first unescaped character for which `stop` holds, failing
+/// on an unescaped character for which `invalid` holds.
+fn escaped_until(input: &str, stop: impl Fn(char) -> bool, invalid: impl Fn(char) -> bool) -> IResult<&str, &str> {
+  let mut chars = input.char_indices();
+  while let Some((index, ch)) = chars.next() {
+    if ch == '\\' && input[index + 1..].starts_with(|next: char| next.is_ascii_punctuation()) {
+      chars.next();
+    } else if stop(ch) {
+      return Ok((&input[index..], &input[..index]));
+    } else if invalid(ch) {
+      break;
+    }
+  }
+  Err(Err::Error(Error::new(input, ErrorKind::Escaped)))
+}
+
+/// `<...>` with no line endings or unescaped `<` or `>` inside.
+fn pointy_destination(input: &str) -> IResult<&str, &str> {
+  delimited(
+    char('<'),
+    |input| escaped_until(input, |ch| ch == '>', |ch| ch == '<' || ch == '\n'),
+    char('>'),
+  )(input)
+}
+
+/// A non-empty run of non-space, non-control characters that doesn't start
+/// with `<`, in which unescaped parentheses are balanced.
+fn raw_destination(input: &str) -> IResult<&str, &str> {
+  let mut depth = 0;
+  let mut chars = input.char_indices().peekable();
+  let mut end = input.len();
+  if input.starts_with('<') {
+    return Err(Err::Error(Error::new(input, ErrorKind::Char)));
+  }
+  while let Some((index, ch)) = chars.next() {
+    match ch {
+      '\\' if chars.peek().is_some_and(|&(_, next)| next.is_ascii_punctuation()) => {
+        chars.next();
+      }
+      '(' => depth += 1,
+      ')' if depth == 0 => {
+        end = index;
+        break;
+      }
+      ')' => depth -= 1,
+      ch if ch == ' ' || ch.is_ascii_control() => {
+        end = index;
+        break;
+      }
+      _ => {}
+    }
+  }
+  if end == 0 || depth > 0 {
+    return Err(Err::Error(Error::new(input, ErrorKind::TakeWhile1)));
+  }
+  Ok((&input[end..], &input[..end]))
+}
+
+fn title(input: &str) -> IResult<&str, &str> {
+  alt((
+    delimited(char('"'), |input| escaped_until(input, |ch| ch == '"', |_| false), char('"')),
+    delimited(char('\''), |input| escaped_until(input, |ch| ch == '\'', |_| false), char('\'')),
+    delimited(char('('), |input| escaped_until(input, |ch| ch == ')', |ch| ch == '('), char(')')),
+  ))(input)
+}
+
+/// The content of a link label: at most 999 characters, no unescaped
+/// brackets, and at least one non-whitespace character.
+pub fn label(input: &str) -> IResult<&str, &str> {
+  let (rest, label) = escaped_until(input, |ch| ch == ']', |ch| ch == '[')?;
+  if label.len() > 999 || label.trim().is_empty() {
+    return Err(Err::Error(Error::new(input, ErrorKind::Verify)));
+  }
+  Ok((rest, label))
+}
+
+/// Labels match case-insensitively, with leading and trailing whitespace
+/// removed and inner whitespace collapsed to a single space.
+pub fn normalize_label(label: &str) -> String {
+  label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Parse the `(destination "title")` part of an inline link or image, right
+/// after the closing `]` of the link text.  Backslash escapes and entities in
+/// the destination and title are resolved.
+pub fn link_tail(input: &str) -> IResult<&str, (String, Option<String>)> {
+  let (input, tail) = delimited(
+    pair(char('('), whitespace),
+    opt(pair(alt((pointy_destination, raw_destination)), opt(preceded(whitespace1, title)))),
+    terminated(whitespace, char(')')),
+  )(input)?;
+  let (destination, title) = tail.unwrap_or(("", None));
+  Ok((input, (unescape(destination), title.map(unescape))))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn tail(destination: &str, title: Option<&str>) -> (String, Option<String>) {
+    (destination.to_string(), title.map(str::to_string))
+  }
+
+  #[test]
+  fn test_label() {
+    assert_eq!(label("foo \\] bar]"), Ok(("]", "foo \\] bar")));
+    assert!(label("foo [bar]").is_err());
+    assert!(label(" \n ]").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex536() {
+    assert_eq!(normalize_label("Толпой"), normalize_label("ТОЛПОЙ"));
+    assert_eq!(normalize_label("  Foo\n  bar "), "foo bar");
+  }
+
+  #[test]
+  fn test_commonmark_ex481() {
+    assert_eq!(link_tail("(/uri \"title\")"), Ok(("", tail("/uri", Some("title")))));
+  }
+
+  #[test]
+  fn test_commonmark_ex483() {
+    assert_eq!(link_tail("()"), Ok(("", tail("", None))));
+    assert_eq!(link_tail("(<>)"), Ok(("", tail("", None))));
+  }
+
+  #[test]
+  fn test_commonmark_ex485() {
+    assert!(link_tail("(/my uri)").is_err());
+    assert_eq!(link_tail("(</my uri>)"), Ok(("", tail("/my uri", None))));
+  }
+
+  #[test]
+  fn test_commonmark_ex487() {
+    assert!(link_tail("(foo\nbar)").is_err());
+    assert!(link_tail("(<foo\nbar>)").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex489() {
+    assert_eq!(link_tail("(<b)c>)"), Ok(("", tail("b)c", None))));
+    assert!(link_tail("(<foo\\>)").is_err());
+    assert!(link_tail("(<b>c)").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex493() {
+    assert_eq!(link_tail("(foo(and(bar)))"), Ok(("", tail("foo(and(bar))", None))));
+    assert_eq!(link_tail("(foo\\(and\\(bar\\))"), Ok(("", tail("foo(and(bar)", None))));
+    assert!(link_tail("(foo(and(bar))").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex499() {
+    assert_eq!(link_tail("(foo%20b&auml;)"), Ok(("", tail("foo%20bä", None))));
+    assert_eq!(link_tail("(foo\\bar)"), Ok(("", tail("foo\\bar", None))));
+  }
+
+  #[test]
+  fn test_commonmark_ex501() {
+    for input in ["(/url \"title\")", "(/url 'title')", "(/url (title))"] {
+      assert_eq!(link_tail(input), Ok(("", tail("/url", Some("title")))));
+    }
+  }
+
+  #[test]
+  fn test_commonmark_ex502() {
+    assert_eq!(link_tail("(/url \"title \\\"&quot;\")"), Ok(("", tail("/url", Some("title \"\"")))));
+  }
+
+  #[test]
+  fn test_commonmark_ex504() {
+    assert!(link_tail("(/url \"title \"and\" title\")").is_err());
+    assert_eq!(
+      link_tail("(/url 'title \"and\" title')"),
+      Ok(("", tail("/url", Some("title \"and\" title"))))
+    );
+  }
+
+  #[test]
+  fn test_commonmark_ex506() {
+    assert_eq!(link_tail("(   /uri\n  \"title\"  )"), Ok(("", tail("/uri", Some("title")))));
+  }
+}
@@ -1,21 +1,152 @@
-use nom::{IResult, branch::alt};
-use crate::types::{Inline, Inline::{Text, Emphasis}};
+use nom::{
+  branch::alt,
+  error::{Error, ErrorKind},
+  Err, IResult,
+};
+use crate::options::{Options, SingleTilde};
+use crate::types::{Attributes, Inline};
 
+mod attributes;
+mod autolink;
+mod code;
+mod delimiter;
+mod emoji;
+mod emojis;
+mod entities;
+mod entity;
+mod escape;
+mod footnote;
+mod formula;
+mod highlight;
+mod line_break;
+mod link;
+mod raw_html;
+mod reference;
+mod smart_punctuation;
+mod strikethrough;
+mod sub_superscript;
 mod text;
+mod wiki_link;
 mod emphasis_or_strong;
 
+pub(crate) use attributes::attributes;
+pub(crate) use autolink::destination as autolink_destination;
+pub(crate) use autolink::{extended_autolink, extended_autolink_boundary};
+pub(crate) use delimiter::{is_punctuation, left_flanking, right_flanking};
+pub(crate) use entity::decode as decode_entity;
+pub(crate) use escape::unescape;
+pub(crate) use footnote::footnote_label;
+pub use reference::{commit_sha, issue, issue_key, link_references, mention, repository_issue, Pattern};
+pub use smart_punctuation::smart_punctuation;
+pub use wiki_link::{default_resolver, WikiLinkResolver};
+
+/// With `options.attributes`, an attribute list right after a code span, a
+/// link or an image belongs to it.
+fn trailing_attributes<'a>(options: &Options, input: &'a str) -> (&'a str, Attributes) {
+  match attributes::attributes(input) {
+    Ok(result) if options.attributes => result,
+    _ => (input, Attributes::default()),
+  }
+}
+
+/// Inline constructs that don't contain other inlines and take precedence
+/// over delimiter runs.  `prev` is the character before `input`, if any.
+fn atom<'a>(options: &Options, prev: Option<char>, input: &'a str) -> IResult<&'a str, Inline> {
+  match construct(options, prev, input)? {
+    (input, Inline::Code(code, _)) => {
+      let (input, attributes) = trailing_attributes(options, input);
+      Ok((input, Inline::Code(code, attributes)))
+    }
+    result => Ok(result),
+  }
+}
+
+fn construct<'a>(options: &Options, prev: Option<char>, input: &'a str) -> IResult<&'a str, Inline> {
+  let mut constructs = alt((
+    line_break::line_break,
+    escape::backslash_escape,
+    code::code_span,
+    entity::entity_reference,
+    autolink::autolink,
+    raw_html::raw_html,
+  ));
+  if options.math && input.starts_with('$') {
+    formula::formula(input)
+  } else if options.emoji && input.starts_with(':') {
+    emoji::emoji(input)
+  } else if options.footnotes && input.starts_with("[^") {
+    footnote::footnote_reference(input)
+  } else if options.wiki_links && input.starts_with("[[") {
+    wiki_link::wiki_link(input)
+  } else if autolink::extended_autolink_boundary(prev, input) {
+    alt((constructs, autolink::extended_autolink))(input)
+  } else {
+    constructs(input)
+  }
+}
+
+/// Subscript and superscript, when enabled.  They are tried before delimiter
+/// runs so that a single `~` isn't taken for strikethrough.
+fn script<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Inline> {
+  match input.chars().next() {
+    Some('~') if options.single_tilde == SingleTilde::Subscript => sub_superscript::subscript(options, input),
+    Some('^') if options.superscript => sub_superscript::superscript(options, input),
+    _ => Err(Err::Error(Error::new(input, ErrorKind::Char))),
+  }
+}
+
+/// Parse the single inline construct at the start of `input`.
 pub fn inline(input: &str) -> IResult<&str, Inline> {
-  alt((text::text, emphasis_or_strong::emphasis_or_strong))(input)
+  inline_with_options(&Options::default())(input)
+}
+
+pub fn inline_with_options(options: &Options) -> impl Fn(&str) -> IResult<&str, Inline> + '_ {
+  move |input: &str| {
+    alt((
+      |input| script(options, input),
+      |input| atom(options, None, input),
+      delimiter::delimited(options, None),
+      text::text,
+      text::literal,
+    ))(input)
+  }
+}
+
+/// Parse the whole of `input` as a sequence of inlines, e.g. the content of a
+/// paragraph.  Adjacent texts are merged.
+pub fn inlines(input: &str) -> Vec<Inline> {
+  inlines_with_options(&Options::default(), input)
+}
+
+pub fn inlines_with_options(options: &Options, input: &str) -> Vec<Inline> {
+  let inlines = delimiter::resolve(options, None, input);
+  if options.smart_punctuation {
+    smart_punctuation(inlines)
+  } else {
+    inlines
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::types::Inline::{
+    AutoLink, Code, Emoji, Emphasis, Formula, HardBreak, Image, Link, Literal, RawHTML, SoftBreak, Strike, Strong, Subscript,
+    Superscript, Text, WikiLink,
+  };
+
+  fn text(content: &str) -> Inline {
+    Text(content.to_string())
+  }
+
+  fn link(children: Vec<Inline>, destination: &str) -> Inline {
+    Link(children, destination.to_string(), None, Attributes::default())
+  }
 
   #[test]
   fn test_commonmark_ex350() {
     let input = "*foo bar*";
-    let want = Emphasis(vec![Text("foo bar".to_string())]);
+    let want = Emphasis(vec![text("foo bar")]);
     let (input, result) = inline(input).unwrap();
     assert_eq!(input, "");
     assert_eq!(want, result);
@@ -24,16 +155,13 @@ mod tests {
   #[test]
   fn test_commonmark_ex351() {
     let input = "a * foo bar*";
-    let want = Text("a * foo bar*".to_string());
-    let (input, result) = inline(input).unwrap();
-    assert_eq!(input, "");
-    assert_eq!(want, result);
+    assert_eq!(inlines(input), vec![text("a * foo bar*")]);
   }
 
   #[test]
   fn test_commonmark_ex356() {
     let input = "_foo bar_";
-    let want = Emphasis(vec![Text("foo bar".to_string())]);
+    let want = Emphasis(vec![text("foo bar")]);
     let (input, result) = inline(input).unwrap();
     assert_eq!(input, "");
     assert_eq!(want, result);
@@ -42,25 +170,22 @@ mod tests {
   #[test]
   fn test_commonmark_ex364() {
     let input = "_foo bar_";
-    let want = Emphasis(vec![Text("foo bar".to_string())]);
+    let want = Emphasis(vec![text("foo bar")]);
     let (input, result) = inline(input).unwrap();
     assert_eq!(input, "");
     assert_eq!(want, result);
   }
-  
+
   #[test]
   fn test_commonmark_ex647() {
     let input = "hello $.;'there";
-    let want = Text("hello $.;'there".to_string());
-    let (input, result) = inline(input).unwrap();
-    assert_eq!(input, "");
-    assert_eq!(want, result);
+    assert_eq!(inlines(input), vec![text("hello $.;'there")]);
   }
 
   #[test]
   fn test_commonmark_ex648() {
     let input = "Foo χρῆν";
-    let want = Text("Foo χρῆν".to_string());
+    let want = text("Foo χρῆν");
     let (input, result) = inline(input).unwrap();
     assert_eq!(input, "");
     assert_eq!(want, result);
@@ -69,9 +194,370 @@ mod tests {
   #[test]
   fn test_commonmark_ex649() {
     let input = "Multiple     spaces";
-    let want = Text("Multiple     spaces".to_string());
+    let want = text("Multiple     spaces");
     let (input, result) = inline(input).unwrap();
     assert_eq!(input, "");
     assert_eq!(want, result);
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_inline_single_construct() {
+    let input = "Visit www.commonmark.org/help for more information.";
+    let (input, result) = inline(input).unwrap();
+    assert_eq!(input, "www.commonmark.org/help for more information.");
+    assert_eq!(text("Visit "), result);
+    let (input, result) = inline(input).unwrap();
+    assert_eq!(input, " for more information.");
+    assert_eq!(AutoLink("www.commonmark.org/help".to_string()), result);
+  }
+
+  #[test]
+  fn test_gfm_ex622() {
+    let input = "Visit www.commonmark.org/help for more information.";
+    let want = vec![text("Visit "), AutoLink("www.commonmark.org/help".to_string()), text(" for more information.")];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex600() {
+    let input = "mail <foo@bar.example.com>";
+    let want = vec![text("mail "), AutoLink("foo@bar.example.com".to_string())];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex613() {
+    let input = "Foo <responsive-image src=\"foo.jpg\" />";
+    let want = vec![text("Foo "), RawHTML("<responsive-image src=\"foo.jpg\" />".to_string())];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex617() {
+    let input = "< a><\nfoo><bar/ >";
+    let want = vec![text("< a><"), SoftBreak, text("foo><bar/ >")];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex632() {
+    let input = "foo       \nbaz";
+    assert_eq!(inlines(input), vec![text("foo"), HardBreak, text("baz")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex634() {
+    let input = "foo\\\n     bar";
+    assert_eq!(inlines(input), vec![text("foo"), HardBreak, text("bar")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex646() {
+    let input = "foo \n baz";
+    assert_eq!(inlines(input), vec![text("foo"), SoftBreak, text("baz")]);
+  }
+
+  #[test]
+  fn test_gfm_ex491() {
+    let input = "Hello ~~Hi~~ world!";
+    let want = vec![text("Hello "), Strike(vec![text("Hi")]), text(" world!")];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_subscript_and_superscript() {
+    let options = Options {
+      single_tilde: SingleTilde::Subscript,
+      superscript: true,
+      ..Options::default()
+    };
+    let input = "H~2~O and x^2^";
+    let want = vec![text("H"), Subscript(vec![text("2")]), text("O and x"), Superscript(vec![text("2")])];
+    assert_eq!(inlines_with_options(&options, input), want);
+  }
+
+  #[test]
+  fn test_formula() {
+    let options = Options {
+      math: true,
+      ..Options::default()
+    };
+    let input = "mass $E = mc^2$ energy";
+    let want = vec![text("mass "), Formula("E = mc^2".to_string()), text(" energy")];
+    assert_eq!(inlines_with_options(&options, input), want);
+    assert_eq!(inlines(input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_formula_prices() {
+    let options = Options {
+      math: true,
+      ..Options::default()
+    };
+    let input = "$5 and $10";
+    assert_eq!(inlines_with_options(&options, input), vec![text("$5 and $10")]);
+    let input = "costs $5 and $6";
+    assert_eq!(inlines(input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_formula_dollar_runs() {
+    let options = Options {
+      math: true,
+      ..Options::default()
+    };
+    let input = "text $$x$$ more";
+    assert_eq!(inlines_with_options(&options, input), vec![text(input)]);
+    let input = "$1 ".repeat(5000);
+    assert_eq!(inlines_with_options(&options, &input), vec![text(&input)]);
+  }
+
+  #[test]
+  fn test_commonmark_ex300() {
+    let input = "\\*not emphasized*";
+    assert_eq!(inlines(input), vec![Literal('*'), text("not emphasized*")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex311() {
+    let input = "&copy; &MadeUpEntity; &#35;";
+    assert_eq!(inlines(input), vec![text("© &MadeUpEntity; #")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex341() {
+    let input = "*foo`*`";
+    assert_eq!(inlines(input), vec![text("*foo"), Code("*".to_string(), Attributes::default())]);
+  }
+
+  #[test]
+  fn test_commonmark_ex342() {
+    let input = "[not a `link](/foo`)";
+    assert_eq!(inlines(input), vec![text("[not a "), Code("link](/foo".to_string(), Attributes::default()), text(")")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex347() {
+    let input = "```foo``";
+    assert_eq!(inlines(input), vec![text("```foo``")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex349() {
+    let input = "`foo``bar``";
+    assert_eq!(inlines(input), vec![text("`foo"), Code("bar".to_string(), Attributes::default())]);
+  }
+
+  #[test]
+  fn test_commonmark_ex481() {
+    let input = "[link](/uri \"title\")";
+    let want = vec![Link(vec![text("link")], "/uri".to_string(), Some("title".to_string()), Attributes::default())];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex485() {
+    let input = "[link](/my uri)";
+    assert_eq!(inlines(input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_commonmark_ex507() {
+    let input = "[link] (/uri)";
+    assert_eq!(inlines(input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_commonmark_ex508() {
+    let input = "[link [foo [bar]]](/uri)";
+    assert_eq!(inlines(input), vec![link(vec![text("link [foo [bar]]")], "/uri")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex510() {
+    let input = "[link [bar](/uri)";
+    assert_eq!(inlines(input), vec![text("[link "), link(vec![text("bar")], "/uri")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex511() {
+    let input = "[link \\[bar](/uri)";
+    assert_eq!(inlines(input), vec![link(vec![text("link "), Literal('['), text("bar")], "/uri")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex512() {
+    let input = "[link *foo **bar** `#`*](/uri)";
+    let want = vec![link(
+      vec![
+        text("link "),
+        Emphasis(vec![text("foo "), Strong(vec![text("bar")]), text(" "), Code("#".to_string(), Attributes::default())]),
+      ],
+      "/uri",
+    )];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex513() {
+    let input = "[![moon](moon.jpg)](/uri)";
+    let want = vec![link(vec![Image(vec![text("moon")], "moon.jpg".to_string(), None, Attributes::default())], "/uri")];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex514() {
+    let input = "[foo [bar](/uri)](/uri)";
+    assert_eq!(inlines(input), vec![text("[foo "), link(vec![text("bar")], "/uri"), text("](/uri)")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex515() {
+    let input = "[foo *[bar [baz](/uri)](/uri)*](/uri)";
+    let want = vec![
+      text("[foo "),
+      Emphasis(vec![text("[bar "), link(vec![text("baz")], "/uri"), text("](/uri)")]),
+      text("](/uri)"),
+    ];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex516() {
+    let input = "![[[foo](uri1)](uri2)](uri3)";
+    let want = vec![Image(
+      vec![text("["), link(vec![text("foo")], "uri1"), text("](uri2)")],
+      "uri3".to_string(),
+      None,
+      Attributes::default(),
+    )];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex517() {
+    let input = "*[foo*](/uri)";
+    assert_eq!(inlines(input), vec![text("*"), link(vec![text("foo*")], "/uri")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex519() {
+    let input = "*foo [bar* baz]";
+    assert_eq!(inlines(input), vec![Emphasis(vec![text("foo [bar")]), text(" baz]")]);
+  }
+
+  #[test]
+  fn test_commonmark_ex520() {
+    let input = "[foo <bar attr=\"](baz)\">";
+    assert_eq!(inlines(input), vec![text("[foo "), RawHTML("<bar attr=\"](baz)\">".to_string())]);
+  }
+
+  #[test]
+  fn test_commonmark_ex575() {
+    let input = "My ![foo bar](/path/to/train.jpg  \"title\"   )";
+    let want = vec![
+      text("My "),
+      Image(
+        vec![text("foo bar")],
+        "/path/to/train.jpg".to_string(),
+        Some("title".to_string()),
+        Attributes::default(),
+      ),
+    ];
+    assert_eq!(inlines(input), want);
+  }
+
+  #[test]
+  fn test_commonmark_ex588() {
+    let input = "!\\[foo]";
+    assert_eq!(inlines(input), vec![text("!"), Literal('['), text("foo]")]);
+  }
+
+  #[test]
+  fn test_emoji() {
+    let options = Options {
+      emoji: true,
+      ..Options::default()
+    };
+    let input = "Shipped :tada: :unknown: :+1:";
+    let want = vec![
+      text("Shipped "),
+      Emoji("tada".to_string(), "🎉".to_string()),
+      text(" :unknown: "),
+      Emoji("+1".to_string(), "👍".to_string()),
+    ];
+    assert_eq!(inlines_with_options(&options, input), want);
+  }
+
+  #[test]
+  fn test_emoji_disabled_by_default() {
+    let input = "Shipped :tada:";
+    assert_eq!(inlines(input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_wiki_links() {
+    let options = Options {
+      wiki_links: true,
+      ..Options::default()
+    };
+    let input = "See [[Install Guide#Linux|the guide]] and *[[FAQ]]*.";
+    let want = vec![
+      text("See "),
+      WikiLink("Install Guide".to_string(), Some("Linux".to_string()), Some("the guide".to_string())),
+      text(" and "),
+      Emphasis(vec![WikiLink("FAQ".to_string(), None, None)]),
+      text("."),
+    ];
+    assert_eq!(inlines_with_options(&options, input), want);
+  }
+
+  #[test]
+  fn test_wiki_links_disabled_by_default() {
+    let input = "[[FAQ]]";
+    assert_eq!(inlines(input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_smart_punctuation_option() {
+    let options = Options {
+      smart_punctuation: true,
+      ..Options::default()
+    };
+    let input = "\"Wait\" -- *don't*...";
+    let want = vec![text("“Wait” – "), Emphasis(vec![text("don’t")]), text("…")];
+    assert_eq!(inlines_with_options(&options, input), want);
+    assert_eq!(inlines(input)[0], text("\"Wait\" -- "));
+  }
+
+  #[test]
+  fn test_attributes() {
+    let options = Options {
+      attributes: true,
+      ..Options::default()
+    };
+    let rust = Attributes {
+      classes: vec!["rust".to_string()],
+      ..Attributes::default()
+    };
+    let width = Attributes {
+      pairs: vec![("width".to_string(), "50%".to_string())],
+      ..Attributes::default()
+    };
+    let input = "`code`{.rust} ![x](a.png){width=50%} [a](b){.rust}";
+    let want = vec![
+      Code("code".to_string(), rust.clone()),
+      text(" "),
+      Image(vec![text("x")], "a.png".to_string(), None, width),
+      text(" "),
+      Link(vec![text("a")], "b".to_string(), None, rust),
+    ];
+    assert_eq!(inlines_with_options(&options, input), want);
+  }
+
+  #[test]
+  fn test_attributes_disabled_by_default() {
+    let input = "`code`{.rust}";
+    assert_eq!(inlines(input), vec![Code("code".to_string(), Attributes::default()), text("{.rust}")]);
+  }
+}
@@ -0,0 +1,258 @@
+use nom::{
+  branch::alt,
+  bytes::complete::{tag, take_until, take_while, take_while1},
+  character::complete::{char, satisfy},
+  combinator::{opt, recognize, verify},
+  multi::many0,
+  sequence::{delimited, pair, preceded, tuple},
+  IResult,
+};
+use crate::types::{Inline, Inline::RawHTML};
+
+fn whitespace(input: &str) -> IResult<&str, &str> {
+  take_while(|ch| ch == ' ' || ch == '\t' || ch == '\n')(input)
+}
+
+fn whitespace1(input: &str) -> IResult<&str, &str> {
+  take_while1(|ch| ch == ' ' || ch == '\t' || ch == '\n')(input)
+}
+
+fn tag_name(input: &str) -> IResult<&str, &str> {
+  recognize(pair(
+    satisfy(|ch| ch.is_ascii_alphabetic()),
+    take_while(|ch: char| ch.is_ascii_alphanumeric() || ch == '-'),
+  ))(input)
+}
+
+fn attribute_name(input: &str) -> IResult<&str, &str> {
+  recognize(pair(
+    satisfy(|ch| ch.is_ascii_alphabetic() || ch == '_' || ch == ':'),
+    take_while(|ch: char| ch.is_ascii_alphanumeric() || "_.:-".contains(ch)),
+  ))(input)
+}
+
+fn attribute_value(input: &str) -> IResult<&str, &str> {
+  alt((
+    take_while1(|ch: char| !ch.is_whitespace() && !"\"'=<>`".contains(ch)),
+    recognize(delimited(char('\''), take_while(|ch| ch != '\''), char('\''))),
+    recognize(delimited(char('"'), take_while(|ch| ch != '"'), char('"'))),
+  ))(input)
+}
+
+fn attribute(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((
+    whitespace1,
+    attribute_name,
+    opt(tuple((whitespace, char('='), whitespace, attribute_value))),
+  )))(input)
+}
+
+fn open_tag(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((
+    char('<'),
+    tag_name,
+    many0(attribute),
+    whitespace,
+    opt(char('/')),
+    char('>'),
+  )))(input)
+}
+
+fn closing_tag(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((tag("</"), tag_name, whitespace, char('>'))))(input)
+}
+
+/// `<!--` + text + `-->`, where text does not start with `>` or `->`, does not
+/// end with `-`, and does not contain `--`.
+fn comment(input: &str) -> IResult<&str, &str> {
+  recognize(delimited(
+    tag("<!--"),
+    verify(take_until("--"), |text: &str| {
+      !text.starts_with('>') && !text.starts_with("->") && !text.ends_with('-')
+    }),
+    tag("-->"),
+  ))(input)
+}
+
+fn processing_instruction(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((tag("<?"), take_until("?>"), tag("?>"))))(input)
+}
+
+fn declaration(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((
+    tag("<!"),
+    take_while1(|ch: char| ch.is_ascii_uppercase()),
+    whitespace1,
+    take_while(|ch| ch != '>'),
+    char('>'),
+  )))(input)
+}
+
+fn cdata_section(input: &str) -> IResult<&str, &str> {
+  recognize(preceded(tag("<![CDATA["), pair(take_until("]]>"), tag("]]>"))))(input)
+}
+
+/// Parse a piece of raw inline HTML: an open tag, a closing tag, an HTML
+/// comment, a processing instruction, a declaration or a CDATA section.
+/// The matched text is kept verbatim.
+pub fn raw_html(input: &str) -> IResult<&str, Inline> {
+  let (input, html) = alt((
+    open_tag,
+    closing_tag,
+    comment,
+    processing_instruction,
+    declaration,
+    cdata_section,
+  ))(input)?;
+  Ok((input, RawHTML(html.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_commonmark_ex609() {
+    let input = "<a><bab><c2c>";
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "<bab><c2c>");
+    assert_eq!(RawHTML("<a>".to_string()), result);
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "<c2c>");
+    assert_eq!(RawHTML("<bab>".to_string()), result);
+  }
+
+  #[test]
+  fn test_commonmark_ex611() {
+    let input = "<a  /><b2
+data=\"foo\" >";
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(RawHTML("<a  />".to_string()), result);
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(
+      RawHTML(
+        "<b2
+data=\"foo\" >"
+          .to_string()
+      ),
+      result
+    );
+  }
+
+  #[test]
+  fn test_commonmark_ex612() {
+    let input = "<a foo=\"bar\" bam = 'baz <em>\"</em>'
+_boolean zoop:33=zoop:33 />";
+    let want = RawHTML(input.to_string());
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex613() {
+    let input = "<responsive-image src=\"foo.jpg\" />";
+    let want = RawHTML(input.to_string());
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex614() {
+    assert!(raw_html("<33>").is_err());
+    assert!(raw_html("<__>").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex615() {
+    assert!(raw_html("<a h*#ref=\"hi\">").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex616() {
+    assert!(raw_html("<a href=\"hi'>").is_err());
+    assert!(raw_html("<a href=hi'>").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex617() {
+    assert!(raw_html("< a>").is_err());
+    assert!(raw_html("<bar/ >").is_err());
+    assert!(raw_html("<foo bar=baz\nbim!bop />").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex618() {
+    assert!(raw_html("<a href='bar'title=title>").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex619() {
+    let input = "</a></foo >";
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(RawHTML("</a>".to_string()), result);
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(RawHTML("</foo >".to_string()), result);
+  }
+
+  #[test]
+  fn test_commonmark_ex620() {
+    assert!(raw_html("</a href=\"foo\">").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex621() {
+    let input = "<!-- this is a
+comment - with hyphen -->";
+    let want = RawHTML(input.to_string());
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex622() {
+    assert!(raw_html("<!-- not a comment -- two hyphens -->").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex623() {
+    assert!(raw_html("<!--> foo -->").is_err());
+    assert!(raw_html("<!-- foo--->").is_err());
+  }
+
+  #[test]
+  fn test_commonmark_ex624() {
+    let input = "<?php echo $a; ?>";
+    let want = RawHTML(input.to_string());
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex625() {
+    let input = "<!ELEMENT br EMPTY>";
+    let want = RawHTML(input.to_string());
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex626() {
+    let input = "<![CDATA[>&<]]>";
+    let want = RawHTML(input.to_string());
+    let (input, result) = raw_html(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_commonmark_ex629() {
+    assert!(raw_html("<a href=\"\\\"\">").is_err());
+  }
+}
@@ -0,0 +1,250 @@
+use nom::{
+  bytes::complete::{take_while1, take_while_m_n},
+  character::complete::{char, satisfy},
+  combinator::{recognize, verify},
+  sequence::{pair, preceded, tuple},
+  IResult,
+};
+use crate::types::{Inline, Inline::{Reference, Text}};
+
+type Resolver<'a> = Box<dyn Fn(&str) -> Option<String> + 'a>;
+
+/// A named pattern, such as a mention or an issue key, whose matches in text
+/// become `Inline::Reference` nodes.  `matcher` recognizes a match at the start
+/// of its input; `resolver` maps the match to a URL, or to `None` to leave it
+/// as text.
+pub struct Pattern<'a> {
+  pub name: String,
+  pub matcher: fn(&str) -> IResult<&str, &str>,
+  pub resolver: Resolver<'a>,
+}
+
+impl<'a> Pattern<'a> {
+  pub fn new(
+    name: &str,
+    matcher: fn(&str) -> IResult<&str, &str>,
+    resolver: impl Fn(&str) -> Option<String> + 'a,
+  ) -> Self {
+    Pattern { name: name.to_string(), matcher, resolver: Box::new(resolver) }
+  }
+}
+
+fn is_word(ch: char) -> bool {
+  ch.is_alphanumeric() || ch == '_'
+}
+
+fn name(input: &str) -> IResult<&str, &str> {
+  verify(take_while1(|ch: char| ch.is_ascii_alphanumeric() || ch == '-'), |name: &str| {
+    !name.starts_with('-') && name.len() <= 39
+  })(input)
+}
+
+/// `@user`
+pub fn mention(input: &str) -> IResult<&str, &str> {
+  recognize(pair(char('@'), name))(input)
+}
+
+/// `#123`
+pub fn issue(input: &str) -> IResult<&str, &str> {
+  recognize(preceded(char('#'), take_while1(|ch: char| ch.is_ascii_digit())))(input)
+}
+
+/// `org/repo#45`
+pub fn repository_issue(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((
+    name,
+    char('/'),
+    take_while1(|ch: char| ch.is_ascii_alphanumeric() || "-_.".contains(ch)),
+    issue,
+  )))(input)
+}
+
+/// An issue tracker key such as `PROJ-123`.
+pub fn issue_key(input: &str) -> IResult<&str, &str> {
+  recognize(tuple((
+    satisfy(|ch| ch.is_ascii_uppercase()),
+    take_while1(|ch: char| ch.is_ascii_uppercase() || ch.is_ascii_digit()),
+    char('-'),
+    take_while1(|ch: char| ch.is_ascii_digit()),
+  )))(input)
+}
+
+/// An abbreviated or full commit SHA: 7 to 40 hexadecimal digits.
+pub fn commit_sha(input: &str) -> IResult<&str, &str> {
+  take_while_m_n(7, 40, |ch: char| ch.is_ascii_hexdigit())(input)
+}
+
+/// The last character of `inline` as written, to check the word boundary
+/// before a match in the text after it.
+fn last_char(inline: &Inline) -> Option<char> {
+  match inline {
+    Text(text)
+    | Inline::Code(text, _)
+    | Inline::Formula(text)
+    | Inline::AutoLink(text)
+    | Inline::RawHTML(text)
+    | Inline::Emoji(_, text)
+    | Inline::Abbreviation(text, _)
+    | Reference(_, text, _) => text.chars().last(),
+    Inline::Literal(ch) => Some(*ch),
+    Inline::Link(children, ..)
+    | Inline::RefLink(children, ..)
+    | Inline::Image(children, ..)
+    | Inline::Emphasis(children)
+    | Inline::Strong(children)
+    | Inline::Strike(children)
+    | Inline::Subscript(children)
+    | Inline::Superscript(children)
+    | Inline::Highlight(children) => children.last().and_then(last_char),
+    Inline::WikiLink(target, _, label) => label.as_ref().unwrap_or(target).chars().last(),
+    Inline::HardBreak | Inline::SoftBreak | Inline::FootnoteReference(_) => None,
+  }
+}
+
+/// Split the matches out of `text`.  `prev` is the character before it, from
+/// the node before, if any.
+fn split_text(text: &str, patterns: &[Pattern], prev: Option<char>, result: &mut Vec<Inline>) {
+  let mut start = 0;
+  let mut index = 0;
+  let mut prev = prev;
+  while let Some(ch) = text[index..].chars().next() {
+    if !prev.is_some_and(is_word) {
+      let found = patterns.iter().find_map(|pattern| {
+        let (rest, matched) = (pattern.matcher)(&text[index..]).ok()?;
+        if rest.starts_with(is_word) {
+          return None;
+        }
+        (pattern.resolver)(matched).map(|url| (pattern, matched, url))
+      });
+      if let Some((pattern, matched, url)) = found {
+        if start < index {
+          result.push(Text(text[start..index].to_string()));
+        }
+        result.push(Reference(pattern.name.clone(), matched.to_string(), url));
+        index += matched.len();
+        start = index;
+        prev = matched.chars().last();
+        continue;
+      }
+    }
+    prev = Some(ch);
+    index += ch.len_utf8();
+  }
+  if start < text.len() {
+    result.push(Text(text[start..].to_string()));
+  }
+}
+
+/// Turn the matches of `patterns` in text into `Inline::Reference` nodes.  Code
+/// spans, links and other nodes that aren't text are left alone; the first
+/// pattern matching at a position wins.
+pub fn link_references(inlines: Vec<Inline>, patterns: &[Pattern]) -> Vec<Inline> {
+  references(inlines, patterns, None)
+}
+
+fn references(inlines: Vec<Inline>, patterns: &[Pattern], mut prev: Option<char>) -> Vec<Inline> {
+  let mut result = vec![];
+  for inline in inlines {
+    let last = last_char(&inline).or(prev);
+    match inline {
+      Text(text) => split_text(&text, patterns, prev, &mut result),
+      Inline::Emphasis(children) => result.push(Inline::Emphasis(references(children, patterns, prev))),
+      Inline::Strong(children) => result.push(Inline::Strong(references(children, patterns, prev))),
+      Inline::Strike(children) => result.push(Inline::Strike(references(children, patterns, prev))),
+      Inline::Highlight(children) => result.push(Inline::Highlight(references(children, patterns, prev))),
+      Inline::Subscript(children) => result.push(Inline::Subscript(references(children, patterns, prev))),
+      Inline::Superscript(children) => result.push(Inline::Superscript(references(children, patterns, prev))),
+      inline => result.push(inline),
+    }
+    prev = last;
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::types::Attributes;
+  use crate::types::Inline::{Code, Emphasis, Link};
+
+  fn text(content: &str) -> Inline {
+    Text(content.to_string())
+  }
+
+  fn reference(name: &str, matched: &str, url: &str) -> Inline {
+    Reference(name.to_string(), matched.to_string(), url.to_string())
+  }
+
+  fn patterns() -> Vec<Pattern<'static>> {
+    vec![
+      Pattern::new("mention", mention, |user| Some(format!("https://github.com/{}", &user[1..]))),
+      Pattern::new("repository issue", repository_issue, |issue| {
+        let (repository, number) = issue.split_at(issue.find('#').unwrap());
+        Some(format!("https://github.com/{}/issues/{}", repository, &number[1..]))
+      }),
+      Pattern::new("issue", issue, |issue| Some(format!("/issues/{}", &issue[1..]))),
+      Pattern::new("issue key", issue_key, |key| {
+        if key.starts_with("PROJ-") {
+          Some(format!("https://tracker.example.com/browse/{}", key))
+        } else {
+          None
+        }
+      }),
+    ]
+  }
+
+  #[test]
+  fn test_link_references() {
+    let input = vec![text("Thanks @octocat, fixes #12 and org/repo#45 (PROJ-7, UTF-8).")];
+    let want = vec![
+      text("Thanks "),
+      reference("mention", "@octocat", "https://github.com/octocat"),
+      text(", fixes "),
+      reference("issue", "#12", "/issues/12"),
+      text(" and "),
+      reference("repository issue", "org/repo#45", "https://github.com/org/repo/issues/45"),
+      text(" ("),
+      reference("issue key", "PROJ-7", "https://tracker.example.com/browse/PROJ-7"),
+      text(", UTF-8)."),
+    ];
+    assert_eq!(link_references(input, &patterns()), want);
+  }
+
+  #[test]
+  fn test_word_boundaries() {
+    let input = vec![text("mail me@example.com, C#1 and #12abc")];
+    assert_eq!(link_references(input.clone(), &patterns()), input);
+  }
+
+  #[test]
+  fn test_word_boundary_after_other_nodes() {
+    let input = vec![Emphasis(vec![text("a")]), text("#1 and "), Code("x".to_string(), Attributes::default()), text("@me")];
+    assert_eq!(link_references(input.clone(), &patterns()), input);
+    let input = vec![text("C"), Emphasis(vec![text("#1")]), Emphasis(vec![text("a ")]), text("#2")];
+    let want = vec![text("C"), Emphasis(vec![text("#1")]), Emphasis(vec![text("a ")]), reference("issue", "#2", "/issues/2")];
+    assert_eq!(link_references(input, &patterns()), want);
+  }
+
+  #[test]
+  fn test_skips_code_and_links() {
+    let input = vec![
+      Code("#12".to_string(), Attributes::default()),
+      Link(vec![text("#12")], "/x".to_string(), None, Attributes::default()),
+      text(" "),
+      Emphasis(vec![text("#12")]),
+    ];
+    let want = vec![
+      Code("#12".to_string(), Attributes::default()),
+      Link(vec![text("#12")], "/x".to_string(), None, Attributes::default()),
+      text(" "),
+      Emphasis(vec![reference("issue", "#12", "/issues/12")]),
+    ];
+    assert_eq!(link_references(input, &patterns()), want);
+  }
+
+  #[test]
+  fn test_commit_sha() {
+    assert_eq!(commit_sha("a5c3785ed8d6a35868bc169f07e40e889087fd2e"), Ok(("", "a5c3785ed8d6a35868bc169f07e40e889087fd2e")));
+    assert!(commit_sha("a5c37").is_err());
+  }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use crate::types::{Inline, Inline::*};
+use super::delimiter::{left_flanking, right_flanking};
+
+/// The first or last character of what `inline` displays, used as the context
+/// of a quote next to it.
+fn edge(inline: &Inline, first: bool) -> Option<char> {
+  let pick = |text: &str| if first { text.chars().next() } else { text.chars().last() };
+  match inline {
+    Text(text) | Code(text, _) | Formula(text) | RawHTML(text) | AutoLink(text) => pick(text),
+    Literal(ch) => Some(*ch),
+    HardBreak | SoftBreak => Some('\n'),
+    Emphasis(children) | Strong(children) | Strike(children) | Subscript(children) | Superscript(children)
+    | Highlight(children) | Link(children, ..) | RefLink(children, ..) | Image(children, ..) => {
+      let child = if first { children.first() } else { children.last() };
+      child.and_then(|child| edge(child, first))
+    }
+    _ => Some('a'),
+  }
+}
+
+/// A straight quote together with where it is and how it may pair up.
+struct Quote {
+  node: usize,
+  offset: usize,
+  ch: char,
+  can_open: bool,
+  can_close: bool,
+}
+
+/// Pair up the quotes of one inline sequence like delimiter runs, and return
+/// the curly quote for each of them keyed by node and byte offset.
+fn quotes(inlines: &[Inline], before: Option<char>, after: Option<char>) -> HashMap<(usize, usize), char> {
+  let mut found = vec![];
+  for (node, inline) in inlines.iter().enumerate() {
+    let text = match inline {
+      Text(text) => text,
+      _ => continue,
+    };
+    let outer_prev = if node == 0 { before } else { edge(&inlines[node - 1], false) };
+    let outer_next = inlines.get(node + 1).map_or(after, |next| edge(next, true));
+    for (offset, ch) in text.char_indices() {
+      if ch != '\'' && ch != '"' {
+        continue;
+      }
+      let prev = text[..offset].chars().last().or(outer_prev);
+      let next = text[offset + 1..].chars().next().or(outer_next);
+      let left = left_flanking(prev, next);
+      let right = right_flanking(prev, next);
+      let can_open = left && !right && prev != Some(']') && prev != Some(')');
+      found.push(Quote { node, offset, ch, can_open, can_close: right });
+    }
+  }
+
+  let mut result = HashMap::new();
+  let mut openers: Vec<usize> = vec![];
+  for (index, quote) in found.iter().enumerate() {
+    if quote.can_close {
+      if let Some(position) = openers.iter().rposition(|&opener| found[opener].ch == quote.ch) {
+        let opener = &found[openers[position]];
+        let (open, close) = if quote.ch == '\'' { ('‘', '’') } else { ('“', '”') };
+        result.insert((opener.node, opener.offset), open);
+        result.insert((quote.node, quote.offset), close);
+        openers.truncate(position);
+        continue;
+      }
+    }
+    if quote.can_open {
+      openers.push(index);
+    }
+  }
+  for quote in &found {
+    let unmatched = match quote.ch {
+      '\'' => '’',
+      _ if quote.can_close => '”',
+      _ => '“',
+    };
+    result.entry((quote.node, quote.offset)).or_insert(unmatched);
+  }
+  result
+}
+
+/// A run of `n` hyphens becomes em dashes where it can, en dashes otherwise,
+/// with em dashes first: `---` is `—`, `--` is `–` and `-----` is `—–`.
+fn dashes(n: usize) -> String {
+  let (em, en) = if n.is_multiple_of(3) {
+    (n / 3, 0)
+  } else if n.is_multiple_of(2) {
+    (0, n / 2)
+  } else if n % 3 == 2 {
+    ((n - 2) / 3, 1)
+  } else {
+    ((n - 4) / 3, 2)
+  };
+  "—".repeat(em) + &"–".repeat(en)
+}
+
+fn smarten_text(text: &str, node: usize, quotes: &HashMap<(usize, usize), char>) -> String {
+  let mut result = String::new();
+  let mut rest = text;
+  while let Some(ch) = rest.chars().next() {
+    let offset = text.len() - rest.len();
+    let length = match ch {
+      '\'' | '"' => {
+        result.push(quotes[&(node, offset)]);
+        1
+      }
+      '-' if rest.starts_with("--") => {
+        let n = rest.chars().take_while(|&ch| ch == '-').count();
+        result.push_str(&dashes(n));
+        n
+      }
+      '.' if rest.starts_with("...") => {
+        result.push('…');
+        3
+      }
+      ch => {
+        result.push(ch);
+        ch.len_utf8()
+      }
+    };
+    rest = &rest[length..];
+  }
+  result
+}
+
+fn smarten(inlines: Vec<Inline>, before: Option<char>, after: Option<char>) -> Vec<Inline> {
+  let quotes = quotes(&inlines, before, after);
+  let edges: Vec<(Option<char>, Option<char>)> =
+    inlines.iter().map(|inline| (edge(inline, true), edge(inline, false))).collect();
+  inlines
+    .into_iter()
+    .enumerate()
+    .map(|(node, inline)| {
+      let prev = if node == 0 { before } else { edges[node - 1].1 };
+      let next = edges.get(node + 1).map_or(after, |edges| edges.0);
+      match inline {
+        Text(text) => Text(smarten_text(&text, node, &quotes)),
+        Emphasis(children) => Emphasis(smarten(children, prev, next)),
+        Strong(children) => Strong(smarten(children, prev, next)),
+        Strike(children) => Strike(smarten(children, prev, next)),
+        Subscript(children) => Subscript(smarten(children, prev, next)),
+        Superscript(children) => Superscript(smarten(children, prev, next)),
+        Highlight(children) => Highlight(smarten(children, prev, next)),
+        Link(children, destination, title, attributes) => {
+          Link(smarten(children, None, None), destination, title, attributes)
+        }
+        Image(children, destination, title, attributes) => {
+          Image(smarten(children, None, None), destination, title, attributes)
+        }
+        inline => inline,
+      }
+    })
+    .collect()
+}
+
+/// Replace straight quotes with curly ones, `--` and `---` with en and em
+/// dashes, and `...` with an ellipsis.  Only `Inline::Text` is changed, so
+/// code, math, raw HTML and escaped characters keep their straight forms.
+pub fn smart_punctuation(inlines: Vec<Inline>) -> Vec<Inline> {
+  smarten(inlines, None, None)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::inline::{inlines, inlines_with_options};
+  use crate::options::Options;
+  use crate::types::Attributes;
+
+  fn smart(input: &str) -> Vec<Inline> {
+    smart_punctuation(inlines(input))
+  }
+
+  fn text(content: &str) -> Inline {
+    Text(content.to_string())
+  }
+
+  #[test]
+  fn test_smart_punct_ex1() {
+    let input = "\"Hello,\" said the spider.\n\"'Shelob' is my name.\"";
+    let want = vec![text("“Hello,” said the spider."), SoftBreak, text("“‘Shelob’ is my name.”")];
+    assert_eq!(smart(input), want);
+  }
+
+  #[test]
+  fn test_smart_punct_ex2() {
+    assert_eq!(smart("'A', 'B', and 'C' are letters."), vec![text("‘A’, ‘B’, and ‘C’ are letters.")]);
+  }
+
+  #[test]
+  fn test_smart_punct_ex4() {
+    assert_eq!(smart("'He said, \"I want to go.\"'"), vec![text("‘He said, “I want to go.”’")]);
+  }
+
+  #[test]
+  fn test_smart_punct_ex5() {
+    assert_eq!(smart("Were you alive in the 70's?"), vec![text("Were you alive in the 70’s?")]);
+  }
+
+  #[test]
+  fn test_smart_punct_ex6() {
+    let input = "Here is some quoted '`code`' and a \"[quoted link](url)\".";
+    let want = vec![
+      text("Here is some quoted ‘"),
+      Code("code".to_string(), Attributes::default()),
+      text("’ and a “"),
+      Link(vec![text("quoted link")], "url".to_string(), None, Attributes::default()),
+      text("”."),
+    ];
+    assert_eq!(smart(input), want);
+  }
+
+  #[test]
+  fn test_smart_punct_ex7() {
+    assert_eq!(smart("'tis the season to be 'jolly'"), vec![text("’tis the season to be ‘jolly’")]);
+  }
+
+  #[test]
+  fn test_smart_punct_ex8() {
+    let input = "'We'll use Jane's boat and John's truck,' Jenna said.";
+    assert_eq!(smart(input), vec![text("‘We’ll use Jane’s boat and John’s truck,’ Jenna said.")]);
+  }
+
+  #[test]
+  fn test_smart_punct_ex10() {
+    assert_eq!(smart("[a]'s b'"), vec![text("[a]’s b’")]);
+  }
+
+  #[test]
+  fn test_smart_punct_ex11() {
+    let input = "\\\"This is not smart.\\\"";
+    assert_eq!(smart(input), vec![Literal('"'), text("This is not smart."), Literal('"')]);
+  }
+
+  #[test]
+  fn test_smart_punct_dashes() {
+    let input = "em---em en--en one- two-- three--- four---- five----- six------ seven-------";
+    let want = "em—em en–en one- two– three— four–– five—– six—— seven—––";
+    assert_eq!(smart(input), vec![text(want)]);
+  }
+
+  #[test]
+  fn test_smart_punct_escaped_hyphens() {
+    let input = "\\-- \\-\\-";
+    assert_eq!(smart(input), vec![Literal('-'), text("- "), Literal('-'), Literal('-')]);
+  }
+
+  #[test]
+  fn test_smart_punct_ellipses() {
+    assert_eq!(smart("Ellipses...and...and...."), vec![text("Ellipses…and…and….")]);
+    assert_eq!(smart("No ellipses\\.\\.\\."), vec![text("No ellipses"), Literal('.'), Literal('.'), Literal('.')]);
+  }
+
+  #[test]
+  fn test_smart_punct_code_untouched() {
+    let want = vec![Code("\"--\"".to_string(), Attributes::default()), text(" "), Formula("a--b".to_string())];
+    let options = Options {
+      math: true,
+      ..Options::default()
+    };
+    assert_eq!(smart_punctuation(inlines_with_options(&options, "`\"--\"` $a--b$")), want);
+  }
+}
@@ -0,0 +1,101 @@
+use crate::options::{Options, SingleTilde};
+use crate::types::{Inline, Inline::Strike};
+use super::delimiter::{left_flanking, right_flanking, Run};
+
+/// A run of one or two tildes follows the same flanking rules as `*`.  Runs of
+/// three or more tildes never delimit strikethrough, and neither do single
+/// tildes when they are read as subscript.
+pub fn run(options: &Options, length: usize, prev: Option<char>, next: Option<char>) -> Run {
+  let delimits = length == 2 || (length == 1 && options.single_tilde == SingleTilde::Strikethrough);
+  Run {
+    ch: '~',
+    length,
+    original: length,
+    can_open: delimits && left_flanking(prev, next),
+    can_close: delimits && right_flanking(prev, next),
+  }
+}
+
+/// The opening and closing runs must have the same length.
+pub fn can_match(opener: &Run, closer: &Run) -> bool {
+  opener.length == closer.length
+}
+
+pub fn used(_opener: &Run, closer: &Run) -> usize {
+  closer.length
+}
+
+pub fn wrap(_used: usize, children: Vec<Inline>) -> Inline {
+  Strike(children)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::inline::delimiter::{delimited, resolve};
+  use nom::{
+    error::{Error, ErrorKind},
+    Err, IResult,
+  };
+  use crate::types::Inline::{Emphasis, Text};
+
+  fn text(content: &str) -> Inline {
+    Text(content.to_string())
+  }
+
+  fn strikethrough(input: &str) -> IResult<&str, Inline> {
+    if !input.starts_with('~') {
+      return Err(Err::Error(Error::new(input, ErrorKind::Char)));
+    }
+    delimited(&Options::default(), None)(input)
+  }
+
+  #[test]
+  fn test_gfm_ex491() {
+    let input = "~~Hi~~ Hello, world!";
+    let want = Strike(vec![text("Hi")]);
+    let (input, result) = strikethrough(input).unwrap();
+    assert_eq!(input, " Hello, world!");
+    assert_eq!(want, result);
+  }
+
+  #[test]
+  fn test_gfm_ex492() {
+    let input = "This ~~has a";
+    assert_eq!(resolve(&Options::default(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_strikethrough_single_tilde() {
+    let input = "~Hi~ ~~Hello~~";
+    let want = vec![Strike(vec![text("Hi")]), text(" "), Strike(vec![text("Hello")])];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+  }
+
+  #[test]
+  fn test_strikethrough_three_tildes() {
+    let input = "This will ~~~not~~~ strike.";
+    assert_eq!(resolve(&Options::default(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_strikethrough_unequal_runs() {
+    let input = "~~Hi~ Hello";
+    assert_eq!(resolve(&Options::default(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_strikethrough_flanking() {
+    let input = "a ~~ b~~";
+    assert_eq!(resolve(&Options::default(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_strikethrough_with_emphasis() {
+    let input = "~~*foo* bar~~";
+    let want = Strike(vec![Emphasis(vec![text("foo")]), text(" bar")]);
+    let (input, result) = strikethrough(input).unwrap();
+    assert_eq!(input, "");
+    assert_eq!(want, result);
+  }
+}
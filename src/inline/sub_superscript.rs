@@ -0,0 +1,139 @@
+use nom::{
+  character::complete::{char, none_of},
+  combinator::peek,
+  error::{Error, ErrorKind},
+  sequence::pair,
+  Err, IResult,
+};
+use crate::options::Options;
+use crate::types::{Inline, Inline::{Subscript, Superscript}};
+use super::delimiter::resolve;
+
+/// The content between two `delimiter`s: at least one character, with no
+/// unescaped whitespace and no unescaped `delimiter`.  Escaped spaces become
+/// plain spaces.
+fn script_content(delimiter: char, input: &str) -> IResult<&str, String> {
+  let mut content = String::new();
+  let mut chars = input.char_indices();
+  while let Some((index, ch)) = chars.next() {
+    match ch {
+      '\\' => match chars.next() {
+        Some((_, ' ')) => content.push(' '),
+        Some((_, escaped)) if !escaped.is_whitespace() => {
+          content.push('\\');
+          content.push(escaped);
+        }
+        _ => break,
+      },
+      ch if ch == delimiter && index > 0 => return Ok((&input[index..], content)),
+      ch if ch.is_whitespace() || ch == delimiter => break,
+      ch => content.push(ch),
+    }
+  }
+  Err(Err::Error(Error::new(input, ErrorKind::Verify)))
+}
+
+fn script<'a>(
+  options: &Options,
+  delimiter: char,
+  input: &'a str,
+) -> IResult<&'a str, Vec<Inline>> {
+  let (input, _) = char(delimiter)(input)?;
+  let (input, content) = script_content(delimiter, input)?;
+  let (input, _) = char(delimiter)(input)?;
+  Ok((input, resolve(options, None, &content)))
+}
+
+/// Pandoc-style subscript, e.g. `H~2~O`.  `~~` never opens a subscript, so
+/// that `~~foo~~` stays strikethrough.
+pub fn subscript<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Inline> {
+  peek(pair(char('~'), none_of("~")))(input)?;
+  let (input, content) = script(options, '~', input)?;
+  Ok((input, Subscript(content)))
+}
+
+/// Pandoc-style superscript, e.g. `2^10^`.
+pub fn superscript<'a>(options: &Options, input: &'a str) -> IResult<&'a str, Inline> {
+  let (input, content) = script(options, '^', input)?;
+  Ok((input, Superscript(content)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::options::SingleTilde;
+  use crate::types::Inline::{Emphasis, Strike, Text};
+
+  fn text(content: &str) -> Inline {
+    Text(content.to_string())
+  }
+
+  fn pandoc() -> Options {
+    Options {
+      single_tilde: SingleTilde::Subscript,
+      superscript: true,
+      ..Options::default()
+    }
+  }
+
+  #[test]
+  fn test_subscript() {
+    let input = "H~2~O";
+    let want = vec![text("H"), Subscript(vec![text("2")]), text("O")];
+    assert_eq!(resolve(&pandoc(), None, input), want);
+  }
+
+  #[test]
+  fn test_superscript() {
+    let input = "2^10^ is 1024";
+    let want = vec![text("2"), Superscript(vec![text("10")]), text(" is 1024")];
+    assert_eq!(resolve(&pandoc(), None, input), want);
+  }
+
+  #[test]
+  fn test_escaped_spaces() {
+    let input = "P~a\\ cat~";
+    let want = vec![text("P"), Subscript(vec![text("a cat")])];
+    assert_eq!(resolve(&pandoc(), None, input), want);
+  }
+
+  #[test]
+  fn test_unescaped_spaces() {
+    let input = "x^a b^";
+    assert_eq!(resolve(&pandoc(), None, input), vec![text(input)]);
+    let input = "x~a b~";
+    assert_eq!(resolve(&pandoc(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_empty_script() {
+    let input = "x^^ y~~";
+    assert_eq!(resolve(&pandoc(), None, input), vec![text(input)]);
+  }
+
+  #[test]
+  fn test_nested_emphasis() {
+    let input = "x^*2*^";
+    let want = vec![text("x"), Superscript(vec![Emphasis(vec![text("2")])])];
+    assert_eq!(resolve(&pandoc(), None, input), want);
+  }
+
+  #[test]
+  fn test_double_tilde_is_strikethrough() {
+    let input = "~~foo~~ H~2~O";
+    let want = vec![
+      Strike(vec![text("foo")]),
+      text(" H"),
+      Subscript(vec![text("2")]),
+      text("O"),
+    ];
+    assert_eq!(resolve(&pandoc(), None, input), want);
+  }
+
+  #[test]
+  fn test_single_tilde_strikethrough_by_default() {
+    let input = "H~2~O x^2^";
+    let want = vec![text("H"), Strike(vec![text("2")]), text("O x^2^")];
+    assert_eq!(resolve(&Options::default(), None, input), want);
+  }
+}
@@ -1,9 +1,76 @@
-
-#![allow(dead_code)]
-use nom::{IResult, bytes::complete::take_while};
+use nom::{
+  branch::alt,
+  bytes::complete::{take, take_while1},
+  error::{Error, ErrorKind},
+  Err, IResult,
+};
 use crate::types::{Inline, Inline::Text};
+use super::autolink::{extended_autolink, extended_autolink_boundary};
+
+/// Characters that may start an inline construct.
+fn is_special(ch: char) -> bool {
+  "*_`[]!<\\&~=$^:\n".contains(ch)
+}
 
+fn starts_extended_autolink(prev: Option<char>, input: &str) -> bool {
+  extended_autolink_boundary(prev, input) && extended_autolink(input).is_ok()
+}
+
+/// Plain text up to the next special character or extended autolink.  Spaces
+/// right before a line ending are left to the line break.
 pub fn text(input: &str) -> IResult<&str, Inline> {
-  let (input, content) = take_while(|_ch| true)(input)?;
-  Ok((input, Text(content.to_string())))
+  let mut end = input.len();
+  let mut prev = None;
+  for (index, ch) in input.char_indices() {
+    if is_special(ch) || (index > 0 && ch.is_alphanumeric() && starts_extended_autolink(prev, &input[index..])) {
+      end = index;
+      break;
+    }
+    prev = Some(ch);
+  }
+  let mut text = &input[..end];
+  if input[end..].starts_with('\n') && !text.trim_end_matches(' ').is_empty() {
+    text = text.trim_end_matches(' ');
+  }
+  if text.is_empty() {
+    return Err(Err::Error(Error::new(input, ErrorKind::TakeWhile1)));
+  }
+  Ok((&input[text.len()..], Text(text.to_string())))
+}
+
+/// A special character that doesn't start any construct is taken literally.
+/// A backtick string is taken as a whole, so that a shorter string inside it
+/// can't open a code span.
+pub fn literal(input: &str) -> IResult<&str, Inline> {
+  let (rest, text) = alt((take_while1(|ch| ch == '`'), take(1usize)))(input)?;
+  Ok((rest, Text(text.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_text_stops_at_special_characters() {
+    assert_eq!(text("foo *bar*"), Ok(("*bar*", Text("foo ".to_string()))));
+    assert_eq!(text("Foo χρῆν"), Ok(("", Text("Foo χρῆν".to_string()))));
+    assert!(text("[foo]").is_err());
+  }
+
+  #[test]
+  fn test_text_leaves_spaces_before_line_ending() {
+    assert_eq!(text("foo  \nbar"), Ok(("  \nbar", Text("foo".to_string()))));
+  }
+
+  #[test]
+  fn test_text_stops_at_extended_autolink() {
+    let input = "Visit www.commonmark.org/help";
+    assert_eq!(text(input), Ok(("www.commonmark.org/help", Text("Visit ".to_string()))));
+  }
+
+  #[test]
+  fn test_literal_backtick_string() {
+    assert_eq!(literal("```foo``"), Ok(("foo``", Text("```".to_string()))));
+    assert_eq!(literal("!foo"), Ok(("foo", Text("!".to_string()))));
+  }
 }
@@ -0,0 +1,91 @@
+use nom::{
+  bytes::complete::{tag, take_while},
+  character::complete::char,
+  combinator::opt,
+  error::{Error, ErrorKind},
+  sequence::{delimited, preceded, tuple},
+  Err, IResult,
+};
+use crate::types::{Inline, Inline::WikiLink};
+
+fn part<'a>(stop: &'static str) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+  move |input: &'a str| take_while(|ch: char| ch != '\n' && ch != ']' && !stop.contains(ch))(input)
+}
+
+/// Parse a wiki link: `[[Page]]`, `[[Page#Section]]`, `[[Page|shown text]]`
+/// or `[[#Section]]` for a section of the current page.
+pub fn wiki_link(input: &str) -> IResult<&str, Inline> {
+  let (rest, (target, anchor, label)) = delimited(
+    tag("[["),
+    tuple((part("#|["), opt(preceded(char('#'), part("|["))), opt(preceded(char('|'), part("["))))),
+    tag("]]"),
+  )(input)?;
+  let target = target.trim();
+  let anchor = anchor.map(str::trim);
+  let label = label.map(str::trim);
+  if (target.is_empty() && anchor.is_none_or(str::is_empty)) || label.is_some_and(str::is_empty) {
+    return Err(Err::Error(Error::new(input, ErrorKind::Verify)));
+  }
+  let link = WikiLink(target.to_string(), anchor.map(str::to_string), label.map(str::to_string));
+  Ok((rest, link))
+}
+
+/// Maps the target and anchor of a wiki link to a URL when rendering.  Any
+/// `Fn(&str, Option<&str>) -> String` is a resolver.
+pub trait WikiLinkResolver {
+  fn resolve(&self, target: &str, anchor: Option<&str>) -> String;
+}
+
+impl<F: Fn(&str, Option<&str>) -> String> WikiLinkResolver for F {
+  fn resolve(&self, target: &str, anchor: Option<&str>) -> String {
+    self(target, anchor)
+  }
+}
+
+/// The resolver used when none is given: spaces become underscores, as in
+/// `[[Page Name#Some Section]]` → `Page_Name#Some_Section`.
+pub fn default_resolver(target: &str, anchor: Option<&str>) -> String {
+  match anchor {
+    Some(anchor) => format!("{}#{}", target.replace(' ', "_"), anchor.replace(' ', "_")),
+    None => target.replace(' ', "_"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn link(target: &str, anchor: Option<&str>, label: Option<&str>) -> Inline {
+    WikiLink(target.to_string(), anchor.map(str::to_string), label.map(str::to_string))
+  }
+
+  #[test]
+  fn test_wiki_link() {
+    assert_eq!(wiki_link("[[Page Name]] rest"), Ok((" rest", link("Page Name", None, None))));
+  }
+
+  #[test]
+  fn test_wiki_link_label() {
+    assert_eq!(wiki_link("[[Page Name|shown text]]"), Ok(("", link("Page Name", None, Some("shown text")))));
+  }
+
+  #[test]
+  fn test_wiki_link_anchor() {
+    assert_eq!(wiki_link("[[Page#Section]]"), Ok(("", link("Page", Some("Section"), None))));
+    assert_eq!(wiki_link("[[#Section|here]]"), Ok(("", link("", Some("Section"), Some("here")))));
+  }
+
+  #[test]
+  fn test_wiki_link_invalid() {
+    for input in ["[[]]", "[[ | x]]", "[[Page|]]", "[[Page\nName]]", "[[Page]", "[[a [b]]]"] {
+      assert!(wiki_link(input).is_err(), "{}", input);
+    }
+  }
+
+  #[test]
+  fn test_resolvers() {
+    assert_eq!(default_resolver("Page Name", Some("Some Section")), "Page_Name#Some_Section");
+    let resolver = |target: &str, _: Option<&str>| format!("/wiki/{}", target.to_lowercase());
+    assert_eq!(resolver.resolve("Home", None), "/wiki/home");
+  }
+}
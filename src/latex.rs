@@ -0,0 +1,517 @@
+//! LaTeX output.
+use crate::block::Footnotes;
+use crate::inline::{autolink_destination, default_resolver, WikiLinkResolver};
+use crate::render::item_blocks;
+use crate::types::{Attributes, Block, Inline, ListItem, ListType};
+use std::cell::{Cell, RefCell};
+
+/// The packages the rendered body relies on.
+pub const DEFAULT_PREAMBLE: &str = "\\usepackage{amsmath}
+\\usepackage{amssymb}
+\\usepackage{graphicx}
+\\usepackage[normalem]{ulem}
+\\usepackage{xcolor}
+\\usepackage{soul}
+\\usepackage{listings}
+\\usepackage{hyperref}";
+
+/// A document in which `$preamble$` and `$body$` are replaced.
+pub const DEFAULT_TEMPLATE: &str = "\\documentclass{article}
+$preamble$
+\\begin{document}
+$body$
+\\end{document}
+";
+
+/// Options of the LaTeX renderer.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LatexOptions {
+    /// Render code blocks as `lstlisting` with their language instead of
+    /// `verbatim`.
+    pub listings: bool,
+    /// Replaces `DEFAULT_PREAMBLE` in documents.
+    pub preamble: Option<String>,
+    /// Replaces `DEFAULT_TEMPLATE` in documents.
+    pub template: Option<String>,
+}
+
+/// Escape the characters that are special to LaTeX in text.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            '^' => escaped.push_str("\\^{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '<' => escaped.push_str("\\textless{}"),
+            '>' => escaped.push_str("\\textgreater{}"),
+            '|' => escaped.push_str("\\textbar{}"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Escape a URL for `\href` and `\includegraphics`, where only the
+/// characters that break the argument need escaping.
+fn escape_url(url: &str) -> String {
+    let mut escaped = String::with_capacity(url.len());
+    for ch in url.chars() {
+        if "\\{}%#".contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+fn sectioning(level: usize) -> &'static str {
+    match level {
+        1 => "section",
+        2 => "subsection",
+        3 => "subsubsection",
+        4 => "paragraph",
+        _ => "subparagraph",
+    }
+}
+
+fn label(attributes: &Attributes) -> String {
+    match &attributes.id {
+        Some(id) => format!("\\label{{{}}}", escape_url(id)),
+        None => String::new(),
+    }
+}
+
+/// Code in a footnote, where `verbatim` and `lstlisting` can't be used: one
+/// `\texttt` line per line of code.
+fn code_lines(code: &str) -> String {
+    let lines: Vec<String> = code
+        .split('\n')
+        .map(|line| match line {
+            "" => "\\mbox{}".to_string(),
+            line => format!("\\texttt{{{}}}", escape(line).replace(' ', "\\ ")),
+        })
+        .collect();
+    lines.join("\\\\\n")
+}
+
+struct Renderer<'a> {
+    options: &'a LatexOptions,
+    wiki_links: &'a dyn WikiLinkResolver,
+    footnotes: Vec<(&'a str, &'a [Block])>,
+    numbers: Footnotes,
+    /// The `\footnotetext` of the footnotes first referenced in the current
+    /// top-level block, written after it.
+    footnote_texts: RefCell<Vec<String>>,
+    /// The numbers of the footnotes whose text is written.
+    written: RefCell<Vec<usize>>,
+    /// Whether a footnote text is being rendered.
+    in_footnote: Cell<bool>,
+}
+
+impl<'a> Renderer<'a> {
+    fn collect_footnotes(&mut self, blocks: &'a [Block]) {
+        for block in blocks {
+            match block {
+                Block::FootnoteDefinition(label, content) => {
+                    self.footnotes.push((label, content));
+                    self.collect_footnotes(content);
+                }
+                Block::Blockquote(content) => self.collect_footnotes(content),
+                Block::List(items, ..) => {
+                    for item in items {
+                        self.collect_footnotes(item_blocks(item));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn inlines(&self, latex: &mut String, inlines: &[Inline]) {
+        for inline in inlines {
+            self.inline(latex, inline);
+        }
+    }
+
+    fn command(&self, latex: &mut String, command: &str, children: &[Inline]) {
+        latex.push_str(&format!("\\{}{{", command));
+        self.inlines(latex, children);
+        latex.push('}');
+    }
+
+    fn inline(&self, latex: &mut String, inline: &Inline) {
+        match inline {
+            Inline::HardBreak => latex.push_str("\\\\\n"),
+            Inline::SoftBreak => latex.push('\n'),
+            Inline::Text(text) | Inline::Abbreviation(text, _) => latex.push_str(&escape(text)),
+            Inline::Literal(ch) => latex.push_str(&escape(&ch.to_string())),
+            Inline::Code(code, _) => latex.push_str(&format!("\\texttt{{{}}}", escape(code))),
+            Inline::Link(children, url, ..) | Inline::RefLink(children, url, _) => {
+                latex.push_str(&format!("\\href{{{}}}{{", escape_url(url)));
+                self.inlines(latex, children);
+                latex.push('}');
+            }
+            Inline::Image(_, url, ..) => latex.push_str(&format!("\\includegraphics{{{}}}", escape_url(url))),
+            Inline::Emphasis(children) => self.command(latex, "emph", children),
+            Inline::Strong(children) => self.command(latex, "textbf", children),
+            Inline::Strike(children) => self.command(latex, "sout", children),
+            Inline::Subscript(children) => self.command(latex, "textsubscript", children),
+            Inline::Superscript(children) => self.command(latex, "textsuperscript", children),
+            Inline::Highlight(children) => self.command(latex, "hl", children),
+            Inline::Formula(formula) => latex.push_str(&format!("\\({}\\)", formula)),
+            Inline::AutoLink(link) => {
+                let url = autolink_destination(link);
+                if url == *link {
+                    latex.push_str(&format!("\\url{{{}}}", escape_url(link)));
+                } else {
+                    latex.push_str(&format!("\\href{{{}}}{{{}}}", escape_url(&url), escape(link)));
+                }
+            }
+            Inline::RawHTML(_) => {}
+            Inline::Emoji(_, emoji) => latex.push_str(emoji),
+            // Every reference is a mark with the number of the footnote, so
+            // that a footnote referenced twice keeps a single text.
+            Inline::FootnoteReference(label) => {
+                let content = self.footnotes.iter().find(|(defined, _)| defined == label);
+                match (self.numbers.number(label), content) {
+                    (Some(number), Some((_, content))) => {
+                        latex.push_str(&format!("\\footnotemark[{}]", number));
+                        if !self.written.borrow().contains(&number) {
+                            self.written.borrow_mut().push(number);
+                            let in_footnote = self.in_footnote.replace(true);
+                            let mut text = String::new();
+                            self.blocks(&mut text, content, 0);
+                            self.in_footnote.set(in_footnote);
+                            let text = format!("\\footnotetext[{}]{{{}}}", number, text.trim_end());
+                            self.footnote_texts.borrow_mut().push(text);
+                        }
+                    }
+                    _ => latex.push_str(&escape(&format!("[^{}]", label))),
+                }
+            }
+            Inline::WikiLink(target, anchor, label) => {
+                let text = match (label, anchor) {
+                    (Some(label), _) => label.clone(),
+                    (None, Some(anchor)) if target.is_empty() => anchor.clone(),
+                    (None, Some(anchor)) => format!("{}#{}", target, anchor),
+                    (None, None) => target.clone(),
+                };
+                let url = self.wiki_links.resolve(target, anchor.as_deref());
+                latex.push_str(&format!("\\href{{{}}}{{{}}}", escape_url(&url), escape(&text)));
+            }
+            Inline::Reference(_, text, url) => {
+                latex.push_str(&format!("\\href{{{}}}{{{}}}", escape_url(url), escape(text)));
+            }
+        }
+    }
+
+    fn list(&self, latex: &mut String, items: &[ListItem], list_type: &ListType, depth: usize) {
+        let environment = match list_type {
+            ListType::OrderedList => "enumerate",
+            ListType::TaskList | ListType::UnorderedList => "itemize",
+        };
+        latex.push_str(&format!("\\begin{{{}}}\n", environment));
+        if let Some(ListItem::OrderedListItem(_, start)) = items.first() {
+            if *start != 1 && depth < 4 {
+                let counter = ["enumi", "enumii", "enumiii", "enumiv"][depth];
+                latex.push_str(&format!("\\setcounter{{{}}}{{{}}}\n", counter, start.saturating_sub(1)));
+            }
+        }
+        let nested = if *list_type == ListType::OrderedList { depth + 1 } else { depth };
+        for item in items {
+            match item {
+                ListItem::TaskListItem(_, true) => latex.push_str("\\item[$\\boxtimes$] "),
+                ListItem::TaskListItem(_, false) => latex.push_str("\\item[$\\square$] "),
+                _ => latex.push_str("\\item "),
+            }
+            let mut content = String::new();
+            self.blocks(&mut content, item_blocks(item), nested);
+            latex.push_str(content.trim_end());
+            latex.push('\n');
+        }
+        latex.push_str(&format!("\\end{{{}}}\n", environment));
+    }
+
+    /// Render `block` followed by a blank line.  `depth` is the number of
+    /// enclosing ordered lists.
+    fn block(&self, latex: &mut String, block: &Block, depth: usize) {
+        match block {
+            // An image on its own becomes a figure, with its alt text as the
+            // caption.
+            Block::Paragraph(inlines) if matches!(inlines.as_slice(), [Inline::Image(..)]) => {
+                if let [Inline::Image(alt, url, _, attributes)] = inlines.as_slice() {
+                    latex.push_str("\\begin{figure}[htbp]\n\\centering\n");
+                    latex.push_str(&format!("\\includegraphics{{{}}}\n", escape_url(url)));
+                    if !alt.is_empty() {
+                        latex.push_str("\\caption{");
+                        self.inlines(latex, alt);
+                        latex.push_str("}\n");
+                    }
+                    latex.push_str(&label(attributes));
+                    if attributes.id.is_some() {
+                        latex.push('\n');
+                    }
+                    latex.push_str("\\end{figure}\n");
+                }
+            }
+            Block::Paragraph(inlines) => {
+                self.inlines(latex, inlines);
+                latex.push('\n');
+            }
+            Block::Header(inlines, level, attributes) => {
+                let star = if attributes.classes.iter().any(|class| class == "unnumbered") { "*" } else { "" };
+                latex.push_str(&format!("\\{}{}{{", sectioning(*level), star));
+                self.inlines(latex, inlines);
+                latex.push_str(&format!("}}{}\n", label(attributes)));
+            }
+            Block::Blockquote(blocks) => {
+                latex.push_str("\\begin{quote}\n");
+                let mut content = String::new();
+                self.blocks(&mut content, blocks, depth);
+                latex.push_str(content.trim_end());
+                latex.push_str("\n\\end{quote}\n");
+            }
+            Block::CodeBlock(info, code, _) => {
+                let code = code.trim_end_matches('\n');
+                let language = info.as_deref().and_then(|info| info.split_whitespace().next());
+                match language {
+                    _ if self.in_footnote.get() => {
+                        latex.push_str(&code_lines(code));
+                        latex.push('\n');
+                    }
+                    Some(language) if self.options.listings => {
+                        latex.push_str(&format!("\\begin{{lstlisting}}[language={}]\n{}\n\\end{{lstlisting}}\n", language, code))
+                    }
+                    _ if self.options.listings => {
+                        latex.push_str(&format!("\\begin{{lstlisting}}\n{}\n\\end{{lstlisting}}\n", code))
+                    }
+                    _ => latex.push_str(&format!("\\begin{{verbatim}}\n{}\n\\end{{verbatim}}\n", code)),
+                }
+            }
+            Block::List(items, list_type, _) => self.list(latex, items, list_type, depth),
+            Block::Formula(formula) => latex.push_str(&format!("\\[\n{}\n\\]\n", formula)),
+            Block::HorizontalRule => latex.push_str("\\begin{center}\\rule{0.5\\linewidth}{0.5pt}\\end{center}\n"),
+            Block::LinkRefence(..) | Block::FootnoteDefinition(..) | Block::AbbreviationDefinition(..) => return,
+        }
+        latex.push('\n');
+    }
+
+    fn blocks(&self, latex: &mut String, blocks: &[Block], depth: usize) {
+        for block in blocks {
+            self.block(latex, block, depth);
+        }
+    }
+}
+
+/// Render `blocks` as the body of a LaTeX document.
+pub fn render(blocks: &[Block]) -> String {
+    render_with_options(&LatexOptions::default(), blocks)
+}
+
+pub fn render_with_options(options: &LatexOptions, blocks: &[Block]) -> String {
+    render_with_resolver(options, blocks, &default_resolver)
+}
+
+/// Like `render_with_options`, with wiki links resolved to URLs by
+/// `resolver`.
+pub fn render_with_resolver(options: &LatexOptions, blocks: &[Block], resolver: &dyn WikiLinkResolver) -> String {
+    let mut renderer = Renderer {
+        options,
+        wiki_links: resolver,
+        footnotes: vec![],
+        numbers: Footnotes::new(blocks),
+        footnote_texts: RefCell::new(vec![]),
+        written: RefCell::new(vec![]),
+        in_footnote: Cell::new(false),
+    };
+    renderer.collect_footnotes(blocks);
+    let mut latex = String::new();
+    for block in blocks {
+        renderer.block(&mut latex, block, 0);
+        for text in renderer.footnote_texts.borrow_mut().drain(..) {
+            latex.push_str(&format!("{}\n\n", text));
+        }
+    }
+    latex.trim_end().to_string() + "\n"
+}
+
+/// Render `blocks` as a whole document: the body and the preamble are put
+/// into the template, replacing `$body$` and `$preamble$`.
+pub fn render_document(options: &LatexOptions, blocks: &[Block]) -> String {
+    let template = options.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+    let preamble = options.preamble.as_deref().unwrap_or(DEFAULT_PREAMBLE);
+    let body = render_with_options(options, blocks);
+    template.replace("$preamble$", preamble).replace("$body$", body.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::{blocks, blocks_with_options};
+    use crate::options::Options;
+    use crate::types::Block::{List, Paragraph};
+    use crate::types::Inline::Text;
+
+    fn markdown(input: &str) -> String {
+        render(&blocks_with_options(&Options { math: true, ..Options::default() }, input))
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("50% of $x & y_1 #2 {a} ~ ^ \\"),
+            "50\\% of \\$x \\& y\\_1 \\#2 \\{a\\} \\textasciitilde{} \\^{} \\textbackslash{}"
+        );
+    }
+
+    #[test]
+    fn test_headings() {
+        let options = Options { attributes: true, ..Options::default() };
+        let input = "# Intro {#intro}
+
+## Details
+
+#### Aside {.unnumbered}
+";
+        let want = "\\section{Intro}\\label{intro}
+
+\\subsection{Details}
+
+\\paragraph*{Aside}
+";
+        assert_eq!(render(&blocks_with_options(&options, input)), want);
+    }
+
+    #[test]
+    fn test_inlines() {
+        let input = "Some *emph*, **strong**, ~~gone~~, `a_b`, $x^2$ and [a link](https://example.com/a#b) <me@example.com>.\n";
+        let want = "Some \\emph{emph}, \\textbf{strong}, \\sout{gone}, \\texttt{a\\_b}, \\(x^2\\) and \
+\\href{https://example.com/a\\#b}{a link} \\href{mailto:me@example.com}{me@example.com}.
+";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        let input = "```rust
+fn main() {}
+```
+";
+        assert_eq!(markdown(input), "\\begin{verbatim}\nfn main() {}\n\\end{verbatim}\n");
+        let options = LatexOptions { listings: true, ..LatexOptions::default() };
+        let want = "\\begin{lstlisting}[language=rust]\nfn main() {}\n\\end{lstlisting}\n";
+        assert_eq!(render_with_options(&options, &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_formula() {
+        let input = "$$
+\\sum_i i
+$$
+";
+        assert_eq!(markdown(input), "\\[\n\\sum_i i\n\\]\n");
+    }
+
+    #[test]
+    fn test_figure() {
+        let input = "![A *cat*](cat.png)\n";
+        let want = "\\begin{figure}[htbp]
+\\centering
+\\includegraphics{cat.png}
+\\caption{A \\emph{cat}}
+\\end{figure}
+";
+        assert_eq!(markdown(input), want);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let options = Options { footnotes: true, ..Options::default() };
+        let input = "Text[^1] and again[^1].
+
+[^1]: The *note*.
+";
+        let want = "Text\\footnotemark[1] and again\\footnotemark[1].
+
+\\footnotetext[1]{The \\emph{note}.}
+";
+        assert_eq!(render(&blocks_with_options(&options, input)), want);
+        let input = "Code[^c].
+
+[^c]: See:
+
+    ```
+    a  b
+
+    ~c
+    ```
+";
+        let want = "Code\\footnotemark[1].
+
+\\footnotetext[1]{See:
+
+\\texttt{a\\ \\ b}\\\\
+\\mbox{}\\\\
+\\texttt{\\textasciitilde{}c}}
+";
+        assert_eq!(render(&blocks_with_options(&options, input)), want);
+    }
+
+    #[test]
+    fn test_wiki_links() {
+        let options = Options { wiki_links: true, ..Options::default() };
+        let resolver = |target: &str, _: Option<&str>| format!("/wiki/{}", target.to_lowercase());
+        let latex = render_with_resolver(&LatexOptions::default(), &blocks_with_options(&options, "[[Home]]"), &resolver);
+        assert_eq!(latex, "\\href{/wiki/home}{Home}\n");
+    }
+
+    #[test]
+    fn test_lists() {
+        let paragraph = |text: &str| vec![Paragraph(vec![Text(text.to_string())])];
+        let tasks = List(
+            vec![ListItem::TaskListItem(paragraph("done"), true), ListItem::TaskListItem(paragraph("todo"), false)],
+            ListType::TaskList,
+            true,
+        );
+        let numbered = List(
+            vec![ListItem::OrderedListItem(paragraph("c"), 3), ListItem::OrderedListItem(paragraph("d"), 4)],
+            ListType::OrderedList,
+            true,
+        );
+        let want = "\\begin{itemize}
+\\item[$\\boxtimes$] done
+\\item[$\\square$] todo
+\\end{itemize}
+
+\\begin{enumerate}
+\\setcounter{enumi}{2}
+\\item c
+\\item d
+\\end{enumerate}
+";
+        assert_eq!(render(&[tasks, numbered]), want);
+    }
+
+    #[test]
+    fn test_document() {
+        let options = LatexOptions {
+            preamble: Some("\\usepackage{hyperref}".to_string()),
+            template: Some("\\documentclass{report}\n$preamble$\n\\begin{document}\n$body$\n\\end{document}\n".to_string()),
+            ..LatexOptions::default()
+        };
+        let want = "\\documentclass{report}
+\\usepackage{hyperref}
+\\begin{document}
+Hello.
+\\end{document}
+";
+        assert_eq!(render_document(&options, &blocks("Hello.")), want);
+        assert!(render_document(&LatexOptions::default(), &blocks("Hello.")).starts_with("\\documentclass{article}"));
+    }
+}
@@ -1,7 +1,20 @@
 extern crate nom;
+pub mod ansi;
 pub mod block;
+pub mod commonmark;
+pub mod html;
 pub mod inline;
+pub mod latex;
+pub mod options;
+#[cfg(feature = "pandoc")]
+pub mod pandoc;
 pub mod parser_combinator;
+pub mod plain_text;
+mod render;
+pub mod roff;
+#[cfg(feature = "serde")]
+pub mod schema;
+pub mod spec;
 pub mod types;
 #[cfg(test)]
 mod tests {}
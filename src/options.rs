@@ -0,0 +1,53 @@
+/// Switches for the syntax extensions that go beyond CommonMark and GFM, or
+/// that conflict with them.  `Options::default()` parses plain GFM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// How a pair of single tildes such as `~foo~` is read.
+    pub single_tilde: SingleTilde,
+    /// Pandoc-style superscript, e.g. `x^2^`.
+    pub superscript: bool,
+    /// GitHub emoji shortcodes, e.g. `:tada:`.
+    pub emoji: bool,
+    /// Footnote references `[^label]` and definitions `[^label]: ...`.
+    pub footnotes: bool,
+    /// Wiki links such as `[[Page Name|shown text]]`.
+    pub wiki_links: bool,
+    /// Highlighted text, e.g. `==marked==`.
+    pub highlight: bool,
+    /// Curly quotes, en and em dashes and ellipses in text.
+    pub smart_punctuation: bool,
+    /// Attribute lists such as `{#id .class}` after headings, code spans,
+    /// links and images.
+    pub attributes: bool,
+    /// Abbreviation definitions such as `*[HTML]: Hyper Text Markup
+    /// Language`, and their expansion in text.
+    pub abbreviations: bool,
+    /// Inline math such as `$e^{i\pi} = -1$`, and display math in `$$`
+    /// blocks or fenced code blocks with the info string `math`.
+    pub math: bool,
+}
+
+/// GFM reads `~foo~` as strikethrough while Pandoc reads it as subscript, so
+/// one of the two has to be picked.  `~~foo~~` is strikethrough either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingleTilde {
+    Strikethrough,
+    Subscript,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            single_tilde: SingleTilde::Strikethrough,
+            superscript: false,
+            emoji: false,
+            footnotes: false,
+            wiki_links: false,
+            highlight: false,
+            smart_punctuation: false,
+            attributes: false,
+            abbreviations: false,
+            math: false,
+        }
+    }
+}
@@ -0,0 +1,905 @@
+//! Conversion to and from Pandoc's JSON AST, with the `pandoc` feature.
+//!
+//! The JSON follows `pandoc-types` 1.23, which is what `pandoc -t json`
+//! writes and `pandoc -f json` reads.  Nodes without a Pandoc equivalent are
+//! written as a `Span` or `Link` with a class naming them, and read back
+//! from one:
+//!
+//! * `Highlight` is a `Span` with the class `mark`, as in Pandoc's own
+//!   `mark` extension, and `Abbreviation` one with the class `abbr` and a
+//!   `title` attribute.
+//! * `Emoji` is a `Span` with the class `emoji` and a `data-emoji`
+//!   attribute holding the shortcode, as Pandoc's `emoji` extension writes.
+//! * `AutoLink` is a `Link` with the class `uri`, or `email` and a
+//!   `mailto:` target for an email address, as Pandoc writes them.
+//!   `WikiLink` is one with the title `wikilink`, and `Reference` one with
+//!   the class `reference` and a `pattern` attribute.
+//! * Footnote references become `Note`s holding the definition.  Reading a
+//!   `Note` gives a numbered reference, with its definition appended to the
+//!   document.
+//! * Raw HTML is a `RawInline` in the `html` format, and a display formula a
+//!   paragraph of `DisplayMath`.
+//! * A code block's language, the first word of its info string, is the
+//!   first class of the `CodeBlock`; the rest of the info string is dropped.
+//! * Task lists are bullet lists whose items start with `☒` or `☐`, as
+//!   Pandoc reads them from GFM.
+//!
+//! Pandoc nodes without an equivalent here are read as their content:
+//! a `Div` as its blocks, a `Span`, `SmallCaps` or `Cite` as its inlines,
+//! `Underline` as emphasis, and a table as one paragraph per row.  Raw
+//! content in formats other than HTML is dropped.  Link reference and
+//! abbreviation definitions are not written, since the links and
+//! abbreviations that use them are already resolved.
+use crate::inline::autolink_destination;
+use crate::render::item_blocks;
+use crate::types::{Attributes, Block, Inline, ListItem, ListType};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The `pandoc-types` API version of the JSON.
+pub const API_VERSION: [u64; 2] = [1, 23];
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input isn't JSON.
+    Json(serde_json::Error),
+    /// The document has an API version other than 1.23.
+    Version(Vec<u64>),
+    /// The JSON isn't a Pandoc document, e.g. a node lacks its `t` or `c`.
+    Malformed(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Json(error) => write!(f, "invalid JSON: {}", error),
+            Error::Version(version) => {
+                let version: Vec<String> = version.iter().map(|part| part.to_string()).collect();
+                write!(f, "unsupported pandoc-api-version {}, expected 1.23", version.join("."))
+            }
+            Error::Malformed(message) => write!(f, "malformed Pandoc JSON: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+fn node(tag: &str, content: Value) -> Value {
+    json!({ "t": tag, "c": content })
+}
+
+fn leaf(tag: &str) -> Value {
+    json!({ "t": tag })
+}
+
+fn attr(attributes: &Attributes) -> Value {
+    json!([attributes.id.clone().unwrap_or_default(), attributes.classes, attributes.pairs])
+}
+
+fn class_attr(class: &str, pairs: Vec<(String, String)>) -> Value {
+    json!(["", [class], pairs])
+}
+
+fn empty_attr() -> Value {
+    json!(["", [], []])
+}
+
+struct Writer<'a> {
+    definitions: HashMap<&'a str, &'a [Block]>,
+    /// The footnotes being written, so that a footnote referencing itself
+    /// doesn't recurse forever.
+    active: Vec<&'a str>,
+}
+
+impl<'a> Writer<'a> {
+    fn collect_definitions(&mut self, blocks: &'a [Block]) {
+        for block in blocks {
+            match block {
+                Block::FootnoteDefinition(label, content) => {
+                    self.definitions.entry(label.as_str()).or_insert(&content[..]);
+                    self.collect_definitions(content);
+                }
+                Block::Blockquote(blocks) => self.collect_definitions(blocks),
+                Block::List(items, ..) => {
+                    for item in items {
+                        self.collect_definitions(item_blocks(item));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Split `text` into `Str`s at whitespace, as Pandoc does.  A run of
+    /// whitespace becomes one `Space`, or a `SoftBreak` if it holds a
+    /// newline.  No-break spaces stay in the words.
+    fn text(&self, values: &mut Vec<Value>, text: &str) {
+        let mut word = String::new();
+        for ch in text.chars() {
+            if ch.is_whitespace() && ch != '\u{a0}' {
+                if !word.is_empty() {
+                    values.push(node("Str", json!(std::mem::take(&mut word))));
+                }
+                match values.last_mut() {
+                    Some(last) if *last == leaf("Space") => {
+                        if ch == '\n' {
+                            *last = leaf("SoftBreak");
+                        }
+                    }
+                    Some(last) if *last == leaf("SoftBreak") || *last == leaf("LineBreak") => {}
+                    _ => values.push(leaf(if ch == '\n' { "SoftBreak" } else { "Space" })),
+                }
+            } else {
+                word.push(ch);
+            }
+        }
+        if !word.is_empty() {
+            values.push(node("Str", json!(word)));
+        }
+    }
+
+    fn inlines(&mut self, inlines: &'a [Inline]) -> Vec<Value> {
+        let mut values = vec![];
+        for inline in inlines {
+            self.inline(&mut values, inline);
+        }
+        values
+    }
+
+    fn inline(&mut self, values: &mut Vec<Value>, inline: &'a Inline) {
+        let value = match inline {
+            Inline::HardBreak => leaf("LineBreak"),
+            Inline::SoftBreak => leaf("SoftBreak"),
+            Inline::Text(text) => return self.text(values, text),
+            Inline::Literal(ch) => return self.text(values, &ch.to_string()),
+            Inline::Code(code, attributes) => node("Code", json!([attr(attributes), code])),
+            Inline::Link(children, url, title, attributes) => {
+                let title = title.clone().unwrap_or_default();
+                node("Link", json!([attr(attributes), self.inlines(children), [url, title]]))
+            }
+            Inline::RefLink(children, url, title) => {
+                node("Link", json!([empty_attr(), self.inlines(children), [url, title]]))
+            }
+            Inline::Image(children, url, title, attributes) => {
+                let title = title.clone().unwrap_or_default();
+                node("Image", json!([attr(attributes), self.inlines(children), [url, title]]))
+            }
+            Inline::Emphasis(children) => node("Emph", json!(self.inlines(children))),
+            Inline::Strong(children) => node("Strong", json!(self.inlines(children))),
+            Inline::Strike(children) => node("Strikeout", json!(self.inlines(children))),
+            Inline::Subscript(children) => node("Subscript", json!(self.inlines(children))),
+            Inline::Superscript(children) => node("Superscript", json!(self.inlines(children))),
+            Inline::Formula(formula) => node("Math", json!([leaf("InlineMath"), formula])),
+            Inline::AutoLink(link) => {
+                let content = [node("Str", json!(link))];
+                let url = autolink_destination(link);
+                let class = if url.starts_with("mailto:") && !link.starts_with("mailto:") { "email" } else { "uri" };
+                node("Link", json!([class_attr(class, vec![]), content, [url, ""]]))
+            }
+            Inline::RawHTML(html) => node("RawInline", json!(["html", html])),
+            Inline::Emoji(shortcode, emoji) => {
+                let attributes = class_attr("emoji", vec![("data-emoji".to_string(), shortcode.clone())]);
+                node("Span", json!([attributes, [node("Str", json!(emoji))]]))
+            }
+            Inline::FootnoteReference(label) => match self.definitions.get(label.as_str()) {
+                Some(content) if !self.active.contains(&label.as_str()) => {
+                    self.active.push(label);
+                    let content = self.blocks(content, false);
+                    self.active.pop();
+                    node("Note", json!(content))
+                }
+                _ => return self.text(values, &format!("[^{}]", label)),
+            },
+            Inline::WikiLink(target, anchor, label) => {
+                let url = match anchor {
+                    Some(anchor) => format!("{}#{}", target, anchor),
+                    None => target.clone(),
+                };
+                let mut content = vec![];
+                self.text(&mut content, label.as_ref().unwrap_or(&url));
+                node("Link", json!([class_attr("wikilink", vec![]), content, [url, "wikilink"]]))
+            }
+            Inline::Reference(pattern, text, url) => {
+                let attributes = class_attr("reference", vec![("pattern".to_string(), pattern.clone())]);
+                let mut content = vec![];
+                self.text(&mut content, text);
+                node("Link", json!([attributes, content, [url, ""]]))
+            }
+            Inline::Highlight(children) => node("Span", json!([class_attr("mark", vec![]), self.inlines(children)])),
+            Inline::Abbreviation(text, title) => {
+                let attributes = class_attr("abbr", vec![("title".to_string(), title.clone())]);
+                let mut content = vec![];
+                self.text(&mut content, text);
+                node("Span", json!([attributes, content]))
+            }
+        };
+        values.push(value);
+    }
+
+    fn list_item(&mut self, blocks: &'a [Block], tight: bool, checkbox: Option<bool>) -> Value {
+        let mut values = self.blocks(blocks, tight);
+        if let Some(checked) = checkbox {
+            let mut mark = vec![node("Str", json!(if checked { "☒" } else { "☐" })), leaf("Space")];
+            match values.first_mut().and_then(|first| first.get_mut("c")).and_then(Value::as_array_mut) {
+                Some(inlines) if matches!(blocks.first(), Some(Block::Paragraph(_))) => {
+                    mark.append(inlines);
+                    *inlines = mark;
+                }
+                _ => {
+                    mark.pop();
+                    values.insert(0, node("Plain", json!(mark)));
+                }
+            }
+        }
+        json!(values)
+    }
+
+    fn block(&mut self, block: &'a Block, tight: bool) -> Option<Value> {
+        let value = match block {
+            Block::Paragraph(inlines) => node(if tight { "Plain" } else { "Para" }, json!(self.inlines(inlines))),
+            Block::Header(inlines, level, attributes) => {
+                node("Header", json!([level, attr(attributes), self.inlines(inlines)]))
+            }
+            Block::Blockquote(blocks) => node("BlockQuote", json!(self.blocks(blocks, false))),
+            Block::CodeBlock(info, code, attributes) => {
+                let mut attributes = attributes.clone();
+                let language = info.as_deref().and_then(|info| info.split_whitespace().next());
+                attributes.classes.splice(0..0, language.map(str::to_string));
+                node("CodeBlock", json!([attr(&attributes), code.trim_end_matches('\n')]))
+            }
+            Block::List(items, list_type, tight) => {
+                let values: Vec<Value> = items
+                    .iter()
+                    .map(|item| match item {
+                        ListItem::TaskListItem(blocks, checked) => self.list_item(blocks, *tight, Some(*checked)),
+                        ListItem::OrderedListItem(blocks, _) | ListItem::UnorderedListItem(blocks) => {
+                            self.list_item(blocks, *tight, None)
+                        }
+                    })
+                    .collect();
+                match (list_type, items.first()) {
+                    (ListType::OrderedList, Some(ListItem::OrderedListItem(_, start))) => {
+                        node("OrderedList", json!([[start, leaf("Decimal"), leaf("Period")], values]))
+                    }
+                    _ => node("BulletList", json!(values)),
+                }
+            }
+            Block::Formula(formula) => node("Para", json!([node("Math", json!([leaf("DisplayMath"), formula]))])),
+            Block::HorizontalRule => leaf("HorizontalRule"),
+            Block::LinkRefence(..) | Block::FootnoteDefinition(..) | Block::AbbreviationDefinition(..) => return None,
+        };
+        Some(value)
+    }
+
+    fn blocks(&mut self, blocks: &'a [Block], tight: bool) -> Vec<Value> {
+        blocks.iter().filter_map(|block| self.block(block, tight)).collect()
+    }
+}
+
+/// The Pandoc document of `blocks`, with empty metadata.
+pub fn to_json(blocks: &[Block]) -> Value {
+    let mut writer = Writer { definitions: HashMap::new(), active: vec![] };
+    writer.collect_definitions(blocks);
+    json!({
+        "pandoc-api-version": [API_VERSION[0], API_VERSION[1]],
+        "meta": {},
+        "blocks": writer.blocks(blocks, false),
+    })
+}
+
+/// The Pandoc document of `blocks` as a JSON string, for `pandoc -f json`.
+pub fn to_string(blocks: &[Block]) -> String {
+    to_json(blocks).to_string()
+}
+
+fn malformed<T>(what: &str, value: &Value) -> Result<T, Error> {
+    Err(Error::Malformed(format!("expected {}, found {}", what, value)))
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>, Error> {
+    value.as_array().map_or_else(|| malformed("an array", value), Ok)
+}
+
+fn string(value: &Value) -> Result<&str, Error> {
+    value.as_str().map_or_else(|| malformed("a string", value), Ok)
+}
+
+fn number(value: &Value) -> Result<u64, Error> {
+    value.as_u64().map_or_else(|| malformed("a number", value), Ok)
+}
+
+/// The elements of the array `value`, which must have `N` of them.
+fn fields<const N: usize>(value: &Value) -> Result<&[Value; N], Error> {
+    let elements = array(value)?;
+    <&[Value; N]>::try_from(&elements[..]).map_or_else(|_| malformed(&format!("{} fields", N), value), Ok)
+}
+
+/// The tag and content of a node.  Nodes such as `Space` have no content.
+fn tag(value: &Value) -> Result<(&str, &Value), Error> {
+    match value.get("t").and_then(Value::as_str) {
+        Some(tag) => Ok((tag, value.get("c").unwrap_or(&Value::Null))),
+        None => malformed("a node", value),
+    }
+}
+
+fn read_attr(value: &Value) -> Result<Attributes, Error> {
+    let [id, classes, pairs] = fields(value)?;
+    let id = string(id)?;
+    let classes = array(classes)?.iter().map(|class| string(class).map(str::to_string)).collect::<Result<_, _>>()?;
+    let pairs = array(pairs)?
+        .iter()
+        .map(|pair| {
+            let [key, value] = fields(pair)?;
+            Ok((string(key)?.to_string(), string(value)?.to_string()))
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(Attributes { id: if id.is_empty() { None } else { Some(id.to_string()) }, classes, pairs })
+}
+
+fn read_target(value: &Value) -> Result<(String, String), Error> {
+    let [url, title] = fields(value)?;
+    Ok((string(url)?.to_string(), string(title)?.to_string()))
+}
+
+/// Append `inline` to `inlines`, merging adjacent text.
+fn push_inline(inlines: &mut Vec<Inline>, inline: Inline) {
+    match (inlines.last_mut(), inline) {
+        (Some(Inline::Text(last)), Inline::Text(text)) => last.push_str(&text),
+        (_, inline) => inlines.push(inline),
+    }
+}
+
+/// The text of inlines that are only text, as in the content of an autolink.
+fn only_text(inlines: &[Inline]) -> Option<String> {
+    match inlines {
+        [] => Some(String::new()),
+        [Inline::Text(text)] => Some(text.clone()),
+        _ => None,
+    }
+}
+
+fn pair<'v>(attributes: &'v Attributes, key: &str) -> Option<&'v str> {
+    attributes.pairs.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+}
+
+#[derive(Default)]
+struct Reader {
+    /// The definitions of the footnotes read from `Note`s, numbered from 1.
+    footnotes: Vec<Block>,
+}
+
+impl Reader {
+    fn inlines(&mut self, value: &Value) -> Result<Vec<Inline>, Error> {
+        let mut inlines = vec![];
+        for value in array(value)? {
+            for inline in self.inline(value)? {
+                push_inline(&mut inlines, inline);
+            }
+        }
+        Ok(inlines)
+    }
+
+    /// The inlines of one Pandoc inline.  Nodes read as their content may
+    /// give several.
+    fn inline(&mut self, value: &Value) -> Result<Vec<Inline>, Error> {
+        let (tag, content) = tag(value)?;
+        let inline = match tag {
+            "Str" => Inline::Text(string(content)?.to_string()),
+            "Space" => Inline::Text(" ".to_string()),
+            "SoftBreak" => Inline::SoftBreak,
+            "LineBreak" => Inline::HardBreak,
+            "Emph" | "Underline" => Inline::Emphasis(self.inlines(content)?),
+            "Strong" => Inline::Strong(self.inlines(content)?),
+            "Strikeout" => Inline::Strike(self.inlines(content)?),
+            "Subscript" => Inline::Subscript(self.inlines(content)?),
+            "Superscript" => Inline::Superscript(self.inlines(content)?),
+            "SmallCaps" => return self.inlines(content),
+            "Quoted" => {
+                let [quote, inlines] = fields(content)?;
+                let (open, close) = if tag_name(quote)? == "SingleQuote" { ("‘", "’") } else { ("“", "”") };
+                let mut quoted = vec![Inline::Text(open.to_string())];
+                for inline in self.inlines(inlines)? {
+                    push_inline(&mut quoted, inline);
+                }
+                push_inline(&mut quoted, Inline::Text(close.to_string()));
+                return Ok(quoted);
+            }
+            "Cite" => {
+                let [_, inlines] = fields(content)?;
+                return self.inlines(inlines);
+            }
+            "Code" => {
+                let [attributes, code] = fields(content)?;
+                Inline::Code(string(code)?.to_string(), read_attr(attributes)?)
+            }
+            "Math" => {
+                let [_, formula] = fields(content)?;
+                Inline::Formula(string(formula)?.to_string())
+            }
+            "RawInline" => {
+                let [format, raw] = fields(content)?;
+                match string(format)? {
+                    "html" => Inline::RawHTML(string(raw)?.to_string()),
+                    _ => return Ok(vec![]),
+                }
+            }
+            "Link" => {
+                let [attributes, inlines, target] = fields(content)?;
+                let attributes = read_attr(attributes)?;
+                let children = self.inlines(inlines)?;
+                let (url, title) = read_target(target)?;
+                return Ok(vec![link(attributes, children, url, title)]);
+            }
+            "Image" => {
+                let [attributes, inlines, target] = fields(content)?;
+                let (url, title) = read_target(target)?;
+                let title = if title.is_empty() { None } else { Some(title) };
+                Inline::Image(self.inlines(inlines)?, url, title, read_attr(attributes)?)
+            }
+            "Note" => {
+                let blocks = self.blocks(content)?;
+                let label = (self.footnotes.len() + 1).to_string();
+                self.footnotes.push(Block::FootnoteDefinition(label.clone(), blocks));
+                Inline::FootnoteReference(label)
+            }
+            "Span" => {
+                let [attributes, inlines] = fields(content)?;
+                let attributes = read_attr(attributes)?;
+                let children = self.inlines(inlines)?;
+                let class = |name: &str| attributes.classes.iter().any(|class| class == name);
+                match (only_text(&children), pair(&attributes, "data-emoji"), pair(&attributes, "title")) {
+                    (Some(emoji), Some(shortcode), _) if class("emoji") => Inline::Emoji(shortcode.to_string(), emoji),
+                    (Some(text), _, Some(title)) if class("abbr") => Inline::Abbreviation(text, title.to_string()),
+                    _ if class("mark") => Inline::Highlight(children),
+                    _ => return Ok(children),
+                }
+            }
+            _ => return malformed("an inline", value),
+        };
+        Ok(vec![inline])
+    }
+
+    fn list_items(&mut self, value: &Value) -> Result<(Vec<Vec<Block>>, bool), Error> {
+        let mut items = vec![];
+        let mut tight = true;
+        for item in array(value)? {
+            let item = array(item)?;
+            tight &= item.iter().all(|block| tag(block).is_ok_and(|(tag, _)| tag != "Para"));
+            let mut blocks = vec![];
+            for block in item {
+                blocks.extend(self.block(block)?);
+            }
+            items.push(blocks);
+        }
+        Ok((items, tight))
+    }
+
+    fn block(&mut self, value: &Value) -> Result<Vec<Block>, Error> {
+        let (tag, content) = tag(value)?;
+        let block = match tag {
+            "Plain" | "Para" => match &self.inlines(content)?[..] {
+                [Inline::Formula(formula)] if is_display_math(content) => Block::Formula(formula.clone()),
+                inlines => Block::Paragraph(inlines.to_vec()),
+            },
+            "LineBlock" => {
+                let mut inlines = vec![];
+                for (index, line) in array(content)?.iter().enumerate() {
+                    if index > 0 {
+                        inlines.push(Inline::HardBreak);
+                    }
+                    for inline in self.inlines(line)? {
+                        push_inline(&mut inlines, inline);
+                    }
+                }
+                Block::Paragraph(inlines)
+            }
+            "CodeBlock" => {
+                let [attributes, code] = fields(content)?;
+                // The first class is the language, the others stay attributes.
+                let mut attributes = read_attr(attributes)?;
+                let info = if attributes.classes.is_empty() { String::new() } else { attributes.classes.remove(0) };
+                Block::CodeBlock(Some(info), string(code)?.to_string(), attributes)
+            }
+            "RawBlock" => {
+                let [format, raw] = fields(content)?;
+                match string(format)? {
+                    "html" => Block::Paragraph(vec![Inline::RawHTML(string(raw)?.to_string())]),
+                    _ => return Ok(vec![]),
+                }
+            }
+            "BlockQuote" => Block::Blockquote(self.blocks(content)?),
+            "OrderedList" => {
+                let [attributes, items] = fields(content)?;
+                let start = number(&fields::<3>(attributes)?[0])? as usize;
+                let (items, tight) = self.list_items(items)?;
+                let items = items.into_iter().zip(start..).map(|(blocks, number)| ListItem::OrderedListItem(blocks, number));
+                Block::List(items.collect(), ListType::OrderedList, tight)
+            }
+            "BulletList" => {
+                let (items, tight) = self.list_items(content)?;
+                match items.iter().map(|blocks| checkbox(blocks)).collect::<Option<Vec<bool>>>() {
+                    Some(checked) if !items.is_empty() => {
+                        let items = items.into_iter().zip(checked).map(|(mut blocks, checked)| {
+                            remove_checkbox(&mut blocks);
+                            ListItem::TaskListItem(blocks, checked)
+                        });
+                        Block::List(items.collect(), ListType::TaskList, tight)
+                    }
+                    _ => {
+                        let items = items.into_iter().map(ListItem::UnorderedListItem);
+                        Block::List(items.collect(), ListType::UnorderedList, tight)
+                    }
+                }
+            }
+            "DefinitionList" => {
+                let mut blocks = vec![];
+                for entry in array(content)? {
+                    let [term, definitions] = fields(entry)?;
+                    blocks.push(Block::Paragraph(vec![Inline::Strong(self.inlines(term)?)]));
+                    for definition in array(definitions)? {
+                        blocks.push(Block::Blockquote(self.blocks(definition)?));
+                    }
+                }
+                return Ok(blocks);
+            }
+            "Header" => {
+                let [level, attributes, inlines] = fields(content)?;
+                Block::Header(self.inlines(inlines)?, number(level)? as usize, read_attr(attributes)?)
+            }
+            "HorizontalRule" => Block::HorizontalRule,
+            "Table" => return self.table(content),
+            "Figure" => {
+                let [_, _, blocks] = fields(content)?;
+                return self.blocks(blocks);
+            }
+            "Div" => {
+                let [_, blocks] = fields(content)?;
+                return self.blocks(blocks);
+            }
+            _ => return malformed("a block", value),
+        };
+        Ok(vec![block])
+    }
+
+    /// A table, as a paragraph for each row with the cells separated by
+    /// ` | `.
+    fn table(&mut self, content: &Value) -> Result<Vec<Block>, Error> {
+        let [_, caption, _, head, bodies, foot] = fields(content)?;
+        let mut rows = vec![];
+        rows.extend(array(&fields::<2>(head)?[1])?);
+        for body in array(bodies)? {
+            let [_, _, head, body] = fields(body)?;
+            rows.extend(array(head)?);
+            rows.extend(array(body)?);
+        }
+        rows.extend(array(&fields::<2>(foot)?[1])?);
+        let mut blocks = self.blocks(&fields::<2>(caption)?[1])?;
+        for row in rows {
+            let mut inlines = vec![];
+            for (index, cell) in array(&fields::<2>(row)?[1])?.iter().enumerate() {
+                if index > 0 {
+                    push_inline(&mut inlines, Inline::Text(" | ".to_string()));
+                }
+                for block in self.blocks(&fields::<5>(cell)?[4])? {
+                    if let Block::Paragraph(cell_inlines) = block {
+                        for inline in cell_inlines {
+                            push_inline(&mut inlines, inline);
+                        }
+                    }
+                }
+            }
+            blocks.push(Block::Paragraph(inlines));
+        }
+        Ok(blocks)
+    }
+
+    fn blocks(&mut self, value: &Value) -> Result<Vec<Block>, Error> {
+        let mut blocks = vec![];
+        for block in array(value)? {
+            blocks.extend(self.block(block)?);
+        }
+        Ok(blocks)
+    }
+}
+
+fn tag_name(value: &Value) -> Result<&str, Error> {
+    tag(value).map(|(tag, _)| tag)
+}
+
+fn is_display_math(inlines: &Value) -> bool {
+    let math = inlines.get(0).and_then(|math| math.get("c")).and_then(|content| content.get(0));
+    math.and_then(|math_type| math_type.get("t")).and_then(Value::as_str) == Some("DisplayMath")
+}
+
+/// A Pandoc link, read back into the node it was written from.
+fn link(attributes: Attributes, children: Vec<Inline>, url: String, title: String) -> Inline {
+    let class = |name: &str| attributes.classes.iter().any(|class| class == name);
+    let text = only_text(&children);
+    if title == "wikilink" {
+        let (target, anchor) = match url.find('#') {
+            Some(index) => (url[..index].to_string(), Some(url[index + 1..].to_string())),
+            None => (url.clone(), None),
+        };
+        let label = text.filter(|text| *text != url);
+        return Inline::WikiLink(target, anchor, label);
+    }
+    match (text, pair(&attributes, "pattern")) {
+        (Some(text), Some(pattern)) if class("reference") => Inline::Reference(pattern.to_string(), text, url),
+        (Some(text), _) if (class("uri") || class("email")) && autolink_destination(&text) == url => {
+            Inline::AutoLink(text)
+        }
+        _ => {
+            let attributes = Attributes {
+                classes: attributes.classes.into_iter().filter(|class| class != "uri" && class != "email").collect(),
+                ..attributes
+            };
+            Inline::Link(children, url, if title.is_empty() { None } else { Some(title) }, attributes)
+        }
+    }
+}
+
+/// Whether the item starts with a `☒` or `☐` checkbox, if it does.
+fn checkbox(blocks: &[Block]) -> Option<bool> {
+    match blocks.first() {
+        Some(Block::Paragraph(inlines)) => match inlines.first() {
+            Some(Inline::Text(text)) if text == "☒" || text.starts_with("☒ ") => Some(true),
+            Some(Inline::Text(text)) if text == "☐" || text.starts_with("☐ ") => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn remove_checkbox(blocks: &mut Vec<Block>) {
+    if let Some(Block::Paragraph(inlines)) = blocks.first_mut() {
+        if let Some(Inline::Text(text)) = inlines.first_mut() {
+            let rest = text.chars().skip(1).collect::<String>();
+            match rest.strip_prefix(' ') {
+                Some(rest) if !rest.is_empty() => *text = rest.to_string(),
+                _ => {
+                    inlines.remove(0);
+                }
+            }
+        }
+        if inlines.is_empty() {
+            blocks.remove(0);
+        }
+    }
+}
+
+/// The blocks of a Pandoc document, followed by the definitions of its
+/// footnotes.  The metadata is ignored.
+pub fn from_json(value: &Value) -> Result<Vec<Block>, Error> {
+    let document = value.as_object().map_or_else(|| malformed("a document", value), Ok)?;
+    let version = document.get("pandoc-api-version").map_or_else(|| malformed("a pandoc-api-version", value), Ok)?;
+    let version = array(version)?.iter().map(number).collect::<Result<Vec<u64>, Error>>()?;
+    if version.len() < 2 || version[..2] != API_VERSION {
+        return Err(Error::Version(version));
+    }
+    let mut reader = Reader::default();
+    let blocks = document.get("blocks").map_or_else(|| malformed("blocks", value), Ok)?;
+    let mut blocks = reader.blocks(blocks)?;
+    blocks.append(&mut reader.footnotes);
+    Ok(blocks)
+}
+
+/// The blocks of a Pandoc document in JSON, as written by `pandoc -t json`.
+pub fn from_str(json: &str) -> Result<Vec<Block>, Error> {
+    from_json(&serde_json::from_str(json)?)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::blocks_with_options;
+    use crate::options::Options;
+    use crate::types::Block::{Header, Paragraph};
+    use crate::types::Inline::{Emphasis, Text};
+
+    fn text(text: &str) -> Inline {
+        Text(text.to_string())
+    }
+
+    fn document(blocks: Value) -> Value {
+        json!({ "pandoc-api-version": [1, 23, 1], "meta": {}, "blocks": blocks })
+    }
+
+    #[test]
+    fn test_to_json() {
+        let blocks = vec![
+            Header(vec![text("Hi")], 1, Attributes { id: Some("hi".to_string()), ..Attributes::default() }),
+            Paragraph(vec![text("some  "), Emphasis(vec![text("text")]), Inline::SoftBreak, text("here")]),
+            Paragraph(vec![text("a\tb \n c\u{a0}d"), Inline::HardBreak, text(" e")]),
+            Block::CodeBlock(Some("rust ignore".to_string()), "fn main() {}".to_string(), Attributes::default()),
+        ];
+        let want = json!({
+            "pandoc-api-version": [1, 23],
+            "meta": {},
+            "blocks": [
+                {"t": "Header", "c": [1, ["hi", [], []], [{"t": "Str", "c": "Hi"}]]},
+                {"t": "Para", "c": [
+                    {"t": "Str", "c": "some"},
+                    {"t": "Space"},
+                    {"t": "Emph", "c": [{"t": "Str", "c": "text"}]},
+                    {"t": "SoftBreak"},
+                    {"t": "Str", "c": "here"}
+                ]},
+                {"t": "Para", "c": [
+                    {"t": "Str", "c": "a"},
+                    {"t": "Space"},
+                    {"t": "Str", "c": "b"},
+                    {"t": "SoftBreak"},
+                    {"t": "Str", "c": "c\u{a0}d"},
+                    {"t": "LineBreak"},
+                    {"t": "Str", "c": "e"}
+                ]},
+                {"t": "CodeBlock", "c": [["", ["rust"], []], "fn main() {}"]}
+            ]
+        });
+        assert_eq!(to_json(&blocks), want);
+    }
+
+    #[test]
+    fn test_autolinks() {
+        let blocks = vec![Paragraph(vec![
+            Inline::AutoLink("info@example.com".to_string()),
+            Inline::AutoLink("https://example.com".to_string()),
+        ])];
+        let want = json!([{"t": "Para", "c": [
+            {"t": "Link", "c": [["", ["email"], []], [{"t": "Str", "c": "info@example.com"}], ["mailto:info@example.com", ""]]},
+            {"t": "Link", "c": [["", ["uri"], []], [{"t": "Str", "c": "https://example.com"}], ["https://example.com", ""]]}
+        ]}]);
+        assert_eq!(to_json(&blocks)["blocks"], want);
+        assert_eq!(from_json(&to_json(&blocks)).unwrap(), blocks);
+    }
+
+    #[test]
+    fn test_from_str() {
+        // pandoc -f gfm -t json, for "# Hi\n\n- [ ] a **b**\n- [x] c\n\n1. one\n2. two\n"
+        let json = r#"{"pandoc-api-version":[1,23,1],"meta":{},"blocks":[
+{"t":"Header","c":[1,["hi",[],[]],[{"t":"Str","c":"Hi"}]]},
+{"t":"BulletList","c":[[{"t":"Plain","c":[{"t":"Str","c":"☐"},{"t":"Space"},{"t":"Str","c":"a"},{"t":"Space"},{"t":"Strong","c":[{"t":"Str","c":"b"}]}]}],[{"t":"Plain","c":[{"t":"Str","c":"☒"},{"t":"Space"},{"t":"Str","c":"c"}]}]]},
+{"t":"OrderedList","c":[[1,{"t":"Decimal"},{"t":"Period"}],[[{"t":"Plain","c":[{"t":"Str","c":"one"}]}],[{"t":"Plain","c":[{"t":"Str","c":"two"}]}]]]}]}"#;
+        let item = |inlines| vec![Paragraph(inlines)];
+        let want = vec![
+            Header(vec![text("Hi")], 1, Attributes { id: Some("hi".to_string()), ..Attributes::default() }),
+            Block::List(
+                vec![
+                    ListItem::TaskListItem(item(vec![text("a "), Inline::Strong(vec![text("b")])]), false),
+                    ListItem::TaskListItem(item(vec![text("c")]), true),
+                ],
+                ListType::TaskList,
+                true,
+            ),
+            Block::List(
+                vec![
+                    ListItem::OrderedListItem(item(vec![text("one")]), 1),
+                    ListItem::OrderedListItem(item(vec![text("two")]), 2),
+                ],
+                ListType::OrderedList,
+                true,
+            ),
+        ];
+        assert_eq!(from_str(json).unwrap(), want);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let options = Options {
+            emoji: true,
+            footnotes: true,
+            wiki_links: true,
+            highlight: true,
+            attributes: true,
+            abbreviations: true,
+            ..Options::default()
+        };
+        let input = "# Setup {#setup .big}
+
+==Read== the [[Main Page#Intro|intro]] and [[Home]] :tada: of HTML[^1], see <https://example.com>
+or <info@example.com>
+or [the *docs*](https://example.com/docs \"Docs\"){.external} and ![logo](logo.png).
+
+```rust
+fn main() {}
+```
+
+*[HTML]: Hyper Text Markup Language
+
+[^1]: A note.
+";
+        let mut blocks = blocks_with_options(&options, input);
+        let paragraph = |inlines| vec![Paragraph(inlines)];
+        let code = Inline::Code("code".to_string(), Attributes::default());
+        let formula = Inline::Formula("x^2".to_string());
+        let html = Inline::RawHTML("<b>".to_string());
+        let ordered = vec![
+            ListItem::OrderedListItem(paragraph(vec![code, text(" and "), formula, text(" and "), html]), 3),
+            ListItem::OrderedListItem(
+                paragraph(vec![Inline::Strike(vec![text("gone")]), Inline::HardBreak, text("line")]),
+                4,
+            ),
+        ];
+        let unordered = vec![
+            ListItem::UnorderedListItem(vec![Paragraph(vec![text("loose")]), Block::HorizontalRule]),
+            ListItem::UnorderedListItem(paragraph(vec![text("list")])),
+        ];
+        blocks.push(Block::Blockquote(vec![Block::List(ordered, ListType::OrderedList, true)]));
+        blocks.push(Block::List(unordered, ListType::UnorderedList, false));
+        blocks.push(Block::Formula("e = mc^2".to_string()));
+        let mut want: Vec<Block> = blocks
+            .iter()
+            .filter(|block| !matches!(block, Block::AbbreviationDefinition(..) | Block::FootnoteDefinition(..)))
+            .cloned()
+            .collect();
+        want.extend(blocks.iter().filter(|block| matches!(block, Block::FootnoteDefinition(..))).cloned());
+        assert_eq!(from_json(&to_json(&blocks)).unwrap(), want);
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let json = document(json!([
+            {"t": "Div", "c": [["", ["note"], []], [{"t": "Para", "c": [
+                {"t": "Underline", "c": [{"t": "Str", "c": "u"}]},
+                {"t": "Space"},
+                {"t": "Quoted", "c": [{"t": "DoubleQuote"}, [{"t": "Str", "c": "q"}]]},
+                {"t": "Span", "c": [["", ["x"], []], [{"t": "Str", "c": "s"}]]},
+                {"t": "RawInline", "c": ["tex", "\\LaTeX"]}
+            ]}]]},
+            {"t": "RawBlock", "c": ["latex", "\\newpage"]},
+            {"t": "RawBlock", "c": ["html", "<hr>"]},
+            {"t": "Table", "c": [
+                ["", [], []],
+                [null, []],
+                [[{"t": "AlignDefault"}, {"t": "ColWidthDefault"}], [{"t": "AlignDefault"}, {"t": "ColWidthDefault"}]],
+                [["", [], []], [[["", [], []], [
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1, [{"t": "Plain", "c": [{"t": "Str", "c": "a"}]}]],
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1, [{"t": "Plain", "c": [{"t": "Str", "c": "b"}]}]]
+                ]]]],
+                [[["", [], []], 0, [], [[["", [], []], [
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1, [{"t": "Plain", "c": [{"t": "Str", "c": "1"}]}]],
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1, [{"t": "Plain", "c": [{"t": "Str", "c": "2"}]}]]
+                ]]]]],
+                [["", [], []], []]
+            ]}
+        ]));
+        let want = vec![
+            Paragraph(vec![Emphasis(vec![text("u")]), text(" “q”s")]),
+            Paragraph(vec![Inline::RawHTML("<hr>".to_string())]),
+            Paragraph(vec![text("a | b")]),
+            Paragraph(vec![text("1 | 2")]),
+        ];
+        assert_eq!(from_json(&json).unwrap(), want);
+    }
+
+    #[test]
+    fn test_notes() {
+        let json = document(json!([{"t": "Para", "c": [
+            {"t": "Str", "c": "a"},
+            {"t": "Note", "c": [{"t": "Para", "c": [{"t": "Str", "c": "note"}]}]}
+        ]}]));
+        let want = vec![
+            Paragraph(vec![text("a"), Inline::FootnoteReference("1".to_string())]),
+            Block::FootnoteDefinition("1".to_string(), vec![Paragraph(vec![text("note")])]),
+        ];
+        assert_eq!(from_json(&json).unwrap(), want);
+    }
+
+    #[test]
+    fn test_errors() {
+        let old = json!({ "pandoc-api-version": [1, 22, 4], "meta": {}, "blocks": [] });
+        assert_eq!(from_json(&old).unwrap_err().to_string(), "unsupported pandoc-api-version 1.22.4, expected 1.23");
+        let unknown = document(json!([{"t": "Paragraph", "c": []}]));
+        assert!(matches!(from_json(&unknown), Err(Error::Malformed(_))));
+        assert!(matches!(from_str("{"), Err(Error::Json(_))));
+    }
+}
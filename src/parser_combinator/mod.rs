@@ -1,19 +1,19 @@
-#![allow(dead_code)]
-
 #[macro_use]
 mod macros;
 use nom::error::ParseError;
 use nom::IResult;
-use nom::{InputLength, InputTake, Parser};
+use nom::{InputIter, InputLength, InputTake};
 
 /// A parser similar to `nom::bytes::complete::take_until()`, but accept a
 /// parser as parameter, returns the input slice up to the postion where parser success.
 /// It doesn't consume the pattern. It will return whole input if the pattern wasn't met.
+/// The parser is only tried at character boundaries.
 /// # Example
 /// ```rust
 /// #[macro_use] extern crate nom;
 /// use nom::{Err, error::{Error, ErrorKind}, Needed, IResult};
 /// use nom::bytes::complete::tag;
+/// use pure_markdown::parser_combinator::take_except;
 ///
 /// fn until_eof(s: &str) -> IResult<&str, &str> {
 ///     take_except(tag("eof"))(s)
@@ -23,16 +23,16 @@ use nom::{InputLength, InputTake, Parser};
 /// assert_eq!(until_eof(""), Ok(("", "")));
 /// assert_eq!(until_eof("1eof2eof"), Ok(("eof2eof", "1")));
 /// ```
-pub fn take_except<Input, Output, Error: ParseError<Input>, F: Parser<Input, Output, Error>>(
+pub fn take_except<Input, Output, Error: ParseError<Input>, F>(
     parser: F,
 ) -> impl Fn(Input) -> IResult<Input, Input, Error>
 where
-    Input: InputLength + InputTake,
+    Input: InputIter + InputLength + InputTake,
     F: Fn(Input) -> IResult<Input, Output, Error>,
 {
     move |i: Input| {
         let input = i;
-        for index in 0..input.input_len() {
+        for (index, _) in input.iter_indices() {
             let (rest, _front) = input.take_split(index);
             match parser(rest) {
                 Ok(_) => return Ok(input.take_split(index)),
@@ -68,6 +68,15 @@ mod tests {
         assert_eq!(expect_consumed, result_consumed);
     }
 
+    #[test]
+    fn test_except_non_ascii() {
+        fn exception(i: &str) -> IResult<&str, char> {
+            char('c')(i)
+        }
+        assert_eq!(take_except(exception)("αβc"), Ok(("c", "αβ")));
+        assert_eq!(take_except(exception)("日本"), Ok(("", "日本")));
+    }
+
     #[test]
     fn test_except_with_tag() {
         fn exception(i: &str) -> IResult<&str, &str> {
@@ -0,0 +1,249 @@
+use crate::block::Footnotes;
+use crate::render::prefix_lines;
+use crate::types::{Block, Inline, ListItem};
+
+/// Options of the plain-text renderer.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PlainTextOptions {
+    /// Append the destination of links in parentheses, as in
+    /// `the docs (https://example.com/docs)`.
+    pub urls: bool,
+    /// Truncate the text to at most this many characters, see `truncate`.
+    pub max_length: Option<usize>,
+}
+
+/// Cut `text` down to at most `max_length` characters, ending with `…`.  The
+/// cut is made at the last whitespace that fits, unless that would leave
+/// nothing, in which case the word is cut.
+pub fn truncate(text: &str, max_length: usize) -> String {
+    if text.chars().count() <= max_length {
+        return text.to_string();
+    }
+    if max_length == 0 {
+        return String::new();
+    }
+    let end = text.char_indices().nth(max_length - 1).map_or(text.len(), |(index, _)| index);
+    let mut cut = &text[..end];
+    let at_boundary = text[end..].starts_with(char::is_whitespace);
+    if !at_boundary {
+        if let Some(boundary) = cut.rfind(char::is_whitespace).filter(|&boundary| !cut[..boundary].trim().is_empty()) {
+            cut = &cut[..boundary];
+        }
+    }
+    format!("{}…", cut.trim_end())
+}
+
+struct Renderer<'a> {
+    options: &'a PlainTextOptions,
+    footnotes: Footnotes,
+}
+
+impl Renderer<'_> {
+    fn inlines(&self, text: &mut String, inlines: &[Inline]) {
+        for inline in inlines {
+            self.inline(text, inline);
+        }
+    }
+
+    fn inline(&self, text: &mut String, inline: &Inline) {
+        match inline {
+            Inline::HardBreak => text.push('\n'),
+            Inline::SoftBreak => text.push(' '),
+            Inline::Text(content) | Inline::Code(content, _) | Inline::Formula(content) | Inline::AutoLink(content) => {
+                text.push_str(content)
+            }
+            Inline::Emoji(_, content) | Inline::Reference(_, content, _) | Inline::Abbreviation(content, _) => {
+                text.push_str(content)
+            }
+            Inline::Literal(ch) => text.push(*ch),
+            Inline::RawHTML(_) => {}
+            Inline::Link(children, url, ..) | Inline::RefLink(children, url, _) => {
+                let start = text.len();
+                self.inlines(text, children);
+                if self.options.urls && !url.is_empty() && text[start..] != *url {
+                    text.push_str(&format!(" ({})", url));
+                }
+            }
+            Inline::Image(children, ..)
+            | Inline::Emphasis(children)
+            | Inline::Strong(children)
+            | Inline::Strike(children)
+            | Inline::Subscript(children)
+            | Inline::Superscript(children)
+            | Inline::Highlight(children) => self.inlines(text, children),
+            Inline::FootnoteReference(label) => match self.footnotes.number(label) {
+                Some(number) => text.push_str(&format!("[{}]", number)),
+                None => text.push_str(&format!("[^{}]", label)),
+            },
+            Inline::WikiLink(target, anchor, label) => match (label, anchor) {
+                (Some(label), _) => text.push_str(label),
+                (None, Some(anchor)) if target.is_empty() => text.push_str(anchor),
+                (None, _) => text.push_str(target),
+            },
+        }
+    }
+
+    fn list(&self, items: &[ListItem], tight: bool) -> String {
+        let items: Vec<String> = items
+            .iter()
+            .map(|item| {
+                let (marker, blocks) = match item {
+                    ListItem::TaskListItem(blocks, true) => ("[x] ".to_string(), blocks),
+                    ListItem::TaskListItem(blocks, false) => ("[ ] ".to_string(), blocks),
+                    ListItem::OrderedListItem(blocks, number) => (format!("{}. ", number), blocks),
+                    ListItem::UnorderedListItem(blocks) => ("- ".to_string(), blocks),
+                };
+                let indent = " ".repeat(marker.chars().count());
+                prefix_lines(&self.blocks(blocks), &marker, &indent)
+            })
+            .collect();
+        items.join(if tight { "\n" } else { "\n\n" })
+    }
+
+    /// The text of `block`, without a trailing newline.  Blocks without text
+    /// give an empty string.
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Paragraph(inlines) | Block::Header(inlines, ..) => {
+                let mut text = String::new();
+                self.inlines(&mut text, inlines);
+                text
+            }
+            Block::Blockquote(blocks) => prefix_lines(&self.blocks(blocks), "> ", "> "),
+            Block::CodeBlock(_, code, _) => code.trim_end_matches('\n').to_string(),
+            Block::Formula(formula) => formula.clone(),
+            Block::List(items, _, tight) => self.list(items, *tight),
+            Block::LinkRefence(..)
+            | Block::FootnoteDefinition(..)
+            | Block::AbbreviationDefinition(..)
+            | Block::HorizontalRule => String::new(),
+        }
+    }
+
+    fn blocks(&self, blocks: &[Block]) -> String {
+        let texts: Vec<String> = blocks.iter().map(|block| self.block(block)).filter(|text| !text.is_empty()).collect();
+        texts.join("\n\n")
+    }
+
+    /// The footnotes, numbered as their references, after the text.
+    fn footnotes(&self, blocks: &[Block]) -> Vec<String> {
+        let mut footnotes = vec![];
+        for block in blocks {
+            if let Block::FootnoteDefinition(label, content) = block {
+                if let Some(number) = self.footnotes.number(label) {
+                    let marker = format!("[{}] ", number);
+                    let indent = " ".repeat(marker.len());
+                    footnotes.push((number, prefix_lines(&self.blocks(content), &marker, &indent)));
+                }
+            }
+        }
+        footnotes.sort_by_key(|(number, _)| *number);
+        footnotes.into_iter().map(|(_, text)| text).collect()
+    }
+}
+
+/// The text of `blocks` without markup: blocks are separated by blank lines,
+/// quotes are prefixed with `> `, and list items with their marker.
+pub fn render(blocks: &[Block]) -> String {
+    render_with_options(&PlainTextOptions::default(), blocks)
+}
+
+pub fn render_with_options(options: &PlainTextOptions, blocks: &[Block]) -> String {
+    let renderer = Renderer { options, footnotes: Footnotes::new(blocks) };
+    let mut text = renderer.blocks(blocks);
+    for footnote in renderer.footnotes(blocks) {
+        text.push_str("\n\n");
+        text.push_str(&footnote);
+    }
+    match options.max_length {
+        Some(max_length) => truncate(&text, max_length),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::{blocks, blocks_with_options};
+    use crate::options::Options;
+    use crate::types::Block::{Blockquote, List, Paragraph};
+    use crate::types::Inline::Text;
+    use crate::types::ListType;
+
+    fn paragraph(text: &str) -> Block {
+        Paragraph(vec![Text(text.to_string())])
+    }
+
+    #[test]
+    fn test_render() {
+        let input = "# Release *1.0*
+
+Read [the docs](https://example.com/docs) or see
+![the logo](logo.png) at <https://example.com>.
+
+```
+cargo build
+```
+";
+        let want = "Release 1.0
+
+Read the docs or see the logo at https://example.com.
+
+cargo build";
+        assert_eq!(render(&blocks(input)), want);
+    }
+
+    #[test]
+    fn test_urls() {
+        let options = PlainTextOptions { urls: true, ..PlainTextOptions::default() };
+        let input = "Read [the docs](https://example.com/docs), [https://a.b](https://a.b).\n";
+        let want = "Read the docs (https://example.com/docs), https://a.b.";
+        assert_eq!(render_with_options(&options, &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_quotes_and_lists() {
+        let item = |text: &str| ListItem::UnorderedListItem(vec![paragraph(text)]);
+        let nested = ListItem::OrderedListItem(
+            vec![paragraph("first"), List(vec![item("a"), item("b")], ListType::UnorderedList, true)],
+            1,
+        );
+        let blocks = vec![
+            Blockquote(vec![paragraph("quoted"), paragraph("twice")]),
+            List(vec![nested, ListItem::TaskListItem(vec![paragraph("done")], true)], ListType::OrderedList, false),
+        ];
+        let want = "> quoted
+>
+> twice
+
+1. first
+
+   - a
+   - b
+
+[x] done";
+        assert_eq!(render(&blocks), want);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let options = Options { footnotes: true, ..Options::default() };
+        let input = "Text[^a].
+
+[^a]: The *note*.
+";
+        assert_eq!(render(&blocks_with_options(&options, input)), "Text[1].\n\n[1] The note.");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("the quick brown fox", 12), "the quick…");
+        assert_eq!(truncate("the quick brown fox", 10), "the quick…");
+        assert_eq!(truncate("unbreakable", 5), "unbr…");
+        assert_eq!(truncate("été à la plage", 6), "été à…");
+        let options = PlainTextOptions { max_length: Some(12), ..PlainTextOptions::default() };
+        assert_eq!(render_with_options(&options, &[paragraph("the quick brown fox")]), "the quick…");
+    }
+}
@@ -0,0 +1,43 @@
+//! Helpers shared by the renderers.
+use crate::types::{Block, ListItem};
+
+/// The blocks of a list item, whatever its kind.
+pub(crate) fn item_blocks(item: &ListItem) -> &[Block] {
+    match item {
+        ListItem::TaskListItem(blocks, _) | ListItem::OrderedListItem(blocks, _) | ListItem::UnorderedListItem(blocks) => {
+            blocks
+        }
+    }
+}
+
+/// Prefix every line of `text`: the first one with `first`, the others with
+/// `rest`.  Blank lines only get the trimmed prefix.
+pub(crate) fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+    let mut prefixed = String::new();
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            prefixed.push('\n');
+        }
+        let prefix = if index == 0 { first } else { rest };
+        if line.is_empty() {
+            prefixed.push_str(prefix.trim_end());
+        } else {
+            prefixed.push_str(prefix);
+            prefixed.push_str(line);
+        }
+    }
+    prefixed
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_prefix_lines() {
+        assert_eq!(prefix_lines("a\n\nb", "> ", "> "), "> a\n>\n> b");
+        assert_eq!(prefix_lines("a\nb", "1. ", "   "), "1. a\n   b");
+        assert_eq!(prefix_lines("", "- ", "  "), "-");
+    }
+}
@@ -0,0 +1,484 @@
+use crate::block::Footnotes;
+use crate::types::{Block, Inline, ListItem};
+
+/// The fields of the `.TH` line of a man page.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ManPage {
+    pub title: Option<String>,
+    pub section: Option<String>,
+    pub date: Option<String>,
+    /// Shown in the left footer, e.g. the name and version of the program.
+    pub source: Option<String>,
+    /// Shown in the center header, e.g. `User Commands`.
+    pub manual: Option<String>,
+}
+
+/// Split off the front matter at the start of `input`: `key: value` lines
+/// between two `---` lines.  The keys `title`, `section`, `date`, `source` and
+/// `manual` are used, other keys are ignored.
+pub fn front_matter(input: &str) -> (ManPage, &str) {
+    let mut page = ManPage::default();
+    let body = match input.strip_prefix("---\n") {
+        Some(body) => body,
+        None => return (page, input),
+    };
+    let end = match body.find("\n---\n").map(|end| (end, end + 5)).or_else(|| {
+        body.strip_suffix("\n---").map(|matter| (matter.len(), body.len()))
+    }) {
+        Some(end) => end,
+        None => return (page, input),
+    };
+    for line in body[..end.0].lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim().trim_matches('"').to_string()),
+            None => continue,
+        };
+        match key {
+            "title" => page.title = Some(value),
+            "section" => page.section = Some(value),
+            "date" => page.date = Some(value),
+            "source" => page.source = Some(value),
+            "manual" => page.manual = Some(value),
+            _ => {}
+        }
+    }
+    (page, &body[end.1..])
+}
+
+/// Escape text for roff: backslashes and hyphens, and a `.` or `'` at the
+/// start of a line, which would otherwise start a request.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    push_escaped(&mut escaped, text);
+    escaped
+}
+
+/// Append `text` escaped to `roff`, which tells whether `text` starts a line.
+fn push_escaped(roff: &mut String, text: &str) {
+    let mut line_start = roff.is_empty() || roff.ends_with('\n');
+    for ch in text.chars() {
+        match ch {
+            '\\' => roff.push_str("\\e"),
+            '-' => roff.push_str("\\-"),
+            '.' | '\'' if line_start => {
+                roff.push_str("\\&");
+                roff.push(ch);
+            }
+            ch => roff.push(ch),
+        }
+        line_start = ch == '\n';
+    }
+}
+
+fn quoted(argument: &str) -> String {
+    format!("\"{}\"", escape(argument).replace('"', "\\(dq"))
+}
+
+fn plain_text(inlines: &[Inline]) -> String {
+    let mut text = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::HardBreak | Inline::SoftBreak => text.push(' '),
+            Inline::Text(content) | Inline::Code(content, _) | Inline::Formula(content) | Inline::AutoLink(content) => {
+                text.push_str(content)
+            }
+            Inline::Emoji(_, content) | Inline::Reference(_, content, _) | Inline::Abbreviation(content, _) => {
+                text.push_str(content)
+            }
+            Inline::Literal(ch) => text.push(*ch),
+            Inline::Link(children, ..)
+            | Inline::RefLink(children, ..)
+            | Inline::Image(children, ..)
+            | Inline::Emphasis(children)
+            | Inline::Strong(children)
+            | Inline::Strike(children)
+            | Inline::Subscript(children)
+            | Inline::Superscript(children)
+            | Inline::Highlight(children) => text.push_str(&plain_text(children)),
+            Inline::WikiLink(target, _, label) => text.push_str(label.as_ref().unwrap_or(target)),
+            Inline::RawHTML(_) | Inline::FootnoteReference(_) => {}
+        }
+    }
+    text
+}
+
+/// A first heading in the style of ronn, `name(section) -- description`,
+/// split into its parts.
+fn title_heading(heading: &str) -> (String, Option<String>, Option<String>) {
+    let (name, description) = match heading.split_once(" -- ").or_else(|| heading.split_once(" - ")) {
+        Some((name, description)) => (name.trim(), Some(description.trim().to_string())),
+        None => (heading.trim(), None),
+    };
+    match name.strip_suffix(')').and_then(|name| name.split_once('(')) {
+        Some((name, section)) => (name.to_string(), Some(section.to_string()), description),
+        None => (name.to_string(), None, description),
+    }
+}
+
+fn font(bold: bool, italic: bool) -> &'static str {
+    match (bold, italic) {
+        (false, false) => "\\fR",
+        (true, false) => "\\fB",
+        (false, true) => "\\fI",
+        (true, true) => "\\f(BI",
+    }
+}
+
+struct Renderer {
+    footnotes: Footnotes,
+}
+
+impl Renderer {
+    fn inlines(&self, roff: &mut String, inlines: &[Inline], bold: bool, italic: bool) {
+        for inline in inlines {
+            self.inline(roff, inline, bold, italic);
+        }
+    }
+
+    /// Render `inline` with the font given by `bold` and `italic`.
+    fn inline(&self, roff: &mut String, inline: &Inline, bold: bool, italic: bool) {
+        match inline {
+            Inline::HardBreak => roff.push_str("\n.br\n"),
+            Inline::SoftBreak => roff.push('\n'),
+            Inline::Text(text) | Inline::Formula(text) | Inline::Abbreviation(text, _) | Inline::Emoji(_, text) => {
+                push_escaped(roff, text)
+            }
+            Inline::Literal(ch) => push_escaped(roff, &ch.to_string()),
+            Inline::Strong(children) => {
+                roff.push_str(font(true, italic));
+                self.inlines(roff, children, true, italic);
+                roff.push_str(font(bold, italic));
+            }
+            Inline::Emphasis(children) => {
+                roff.push_str(font(bold, true));
+                self.inlines(roff, children, bold, true);
+                roff.push_str(font(bold, italic));
+            }
+            Inline::Code(code, _) => {
+                roff.push_str(&format!("{}{}{}", font(true, italic), escape(code), font(bold, italic)));
+            }
+            Inline::Link(children, url, ..) | Inline::RefLink(children, url, _) => {
+                self.inlines(roff, children, bold, italic);
+                if plain_text(children) != *url {
+                    roff.push_str(&format!(" <{}{}{}>", font(bold, true), escape(url), font(bold, italic)));
+                }
+            }
+            Inline::Reference(_, text, url) => {
+                push_escaped(roff, text);
+                roff.push_str(&format!(" <{}{}{}>", font(bold, true), escape(url), font(bold, italic)));
+            }
+            Inline::AutoLink(link) => roff.push_str(&format!("<{}{}{}>", font(bold, true), escape(link), font(bold, italic))),
+            Inline::Image(children, ..)
+            | Inline::Strike(children)
+            | Inline::Subscript(children)
+            | Inline::Superscript(children)
+            | Inline::Highlight(children) => self.inlines(roff, children, bold, italic),
+            Inline::WikiLink(..) => push_escaped(roff, &plain_text(std::slice::from_ref(inline))),
+            Inline::FootnoteReference(label) => match self.footnotes.number(label) {
+                Some(number) => roff.push_str(&format!("[{}]", number)),
+                None => push_escaped(roff, &format!("[^{}]", label)),
+            },
+            Inline::RawHTML(_) => {}
+        }
+    }
+
+    fn text(&self, inlines: &[Inline]) -> String {
+        let mut roff = String::new();
+        self.inlines(&mut roff, inlines, false, false);
+        roff
+    }
+
+    fn list(&self, roff: &mut String, items: &[ListItem]) {
+        for item in items {
+            let (tag, blocks) = match item {
+                ListItem::TaskListItem(blocks, true) => ("[x]".to_string(), blocks),
+                ListItem::TaskListItem(blocks, false) => ("[ ]".to_string(), blocks),
+                ListItem::OrderedListItem(blocks, number) => (format!("{}.", number), blocks),
+                ListItem::UnorderedListItem(blocks) => ("\\(bu".to_string(), blocks),
+            };
+            let indent = if let ListItem::UnorderedListItem(_) = item { 2 } else { 4 };
+            roff.push_str(&format!(".IP {} {}\n", tag, indent));
+            for (index, block) in blocks.iter().enumerate() {
+                match block {
+                    Block::Paragraph(inlines) => {
+                        if index > 0 {
+                            roff.push_str(".IP\n");
+                        }
+                        roff.push_str(&self.text(inlines));
+                        roff.push('\n');
+                    }
+                    Block::List(items, ..) => {
+                        roff.push_str(".RS\n");
+                        self.list(roff, items);
+                        roff.push_str(".RE\n");
+                    }
+                    block => {
+                        roff.push_str(".RS\n");
+                        self.block(roff, block);
+                        roff.push_str(".RE\n");
+                    }
+                }
+            }
+        }
+    }
+
+    fn block(&self, roff: &mut String, block: &Block) {
+        match block {
+            Block::Header(inlines, 1, _) => {
+                roff.push_str(&format!(".SH {}\n", quoted(&plain_text(inlines).to_uppercase())));
+            }
+            Block::Header(inlines, 2, _) => roff.push_str(&format!(".SS {}\n", quoted(&plain_text(inlines)))),
+            Block::Header(inlines, ..) => {
+                roff.push_str(&format!(".PP\n\\fB{}\\fR\n", escape(&plain_text(inlines))));
+            }
+            Block::Paragraph(inlines) => {
+                roff.push_str(".PP\n");
+                roff.push_str(&self.text(inlines));
+                roff.push('\n');
+            }
+            Block::Blockquote(blocks) => {
+                roff.push_str(".RS\n");
+                self.blocks(roff, blocks);
+                roff.push_str(".RE\n");
+            }
+            Block::CodeBlock(_, code, _) | Block::Formula(code) => {
+                roff.push_str(".IP\n.nf\n\\f(CR\n");
+                push_escaped(roff, code.trim_end_matches('\n'));
+                roff.push_str("\n\\fR\n.fi\n");
+            }
+            Block::List(items, ..) => {
+                self.list(roff, items);
+                roff.push_str(".PP\n");
+            }
+            Block::HorizontalRule => roff.push_str(".PP\n\\l'\\n(.lu'\n"),
+            Block::LinkRefence(..) | Block::FootnoteDefinition(..) | Block::AbbreviationDefinition(..) => {}
+        }
+    }
+
+    fn blocks(&self, roff: &mut String, blocks: &[Block]) {
+        for block in blocks {
+            self.block(roff, block);
+        }
+    }
+
+    /// The footnotes in a `NOTES` section, numbered as their references.
+    fn notes(&self, roff: &mut String, blocks: &[Block]) {
+        let mut notes: Vec<(usize, &[Block])> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::FootnoteDefinition(label, content) => self.footnotes.number(label).map(|number| (number, &content[..])),
+                _ => None,
+            })
+            .collect();
+        if notes.is_empty() {
+            return;
+        }
+        notes.sort_by_key(|(number, _)| *number);
+        roff.push_str(".SH NOTES\n");
+        for (number, content) in notes {
+            roff.push_str(&format!(".IP [{}] 4\n", number));
+            for block in content {
+                match block {
+                    Block::Paragraph(inlines) => {
+                        roff.push_str(&self.text(inlines));
+                        roff.push('\n');
+                    }
+                    block => self.block(roff, block),
+                }
+            }
+        }
+    }
+}
+
+/// Render `blocks` as a man page, taking the `.TH` line from the first
+/// heading, see `render_with_metadata`.
+pub fn render(blocks: &[Block]) -> String {
+    render_with_metadata(&ManPage::default(), blocks)
+}
+
+/// Render `blocks` as a man page with `man(7)` macros.  Without a title in
+/// `page`, the first heading gives the title and section instead, as in
+/// `# mytool(1) -- do things`, and its description becomes the `NAME`
+/// section.
+pub fn render_with_metadata(page: &ManPage, blocks: &[Block]) -> String {
+    let mut page = page.clone();
+    let mut blocks = blocks;
+    let mut name = None;
+    if page.title.is_none() {
+        if let Some((Block::Header(inlines, ..), rest)) = blocks.split_first() {
+            let (title, section, description) = title_heading(&plain_text(inlines));
+            if let Some(description) = description {
+                name = Some(format!("{} \\- {}", escape(&title), escape(&description)));
+            }
+            page.title = Some(title);
+            page.section = page.section.or(section);
+            blocks = rest;
+        }
+    }
+
+    let fields = [&page.title, &page.section, &page.date, &page.source, &page.manual];
+    let count = fields.iter().rposition(|field| field.is_some()).map_or(0, |last| last + 1);
+    let arguments: Vec<String> = fields[..count]
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let field = field.as_deref().unwrap_or("");
+            quoted(&if index == 0 { field.to_uppercase() } else { field.to_string() })
+        })
+        .collect();
+    let mut roff = String::new();
+    if !arguments.is_empty() {
+        roff.push_str(&format!(".TH {}\n", arguments.join(" ")));
+    }
+    if let Some(name) = name {
+        roff.push_str(&format!(".SH NAME\n.PP\n{}\n", name));
+    }
+    let renderer = Renderer { footnotes: Footnotes::new(blocks) };
+    renderer.blocks(&mut roff, blocks);
+    renderer.notes(&mut roff, blocks);
+    roff
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::{blocks, blocks_with_options};
+    use crate::options::Options;
+    use crate::types::Block::{List, Paragraph};
+    use crate::types::Inline::Text;
+    use crate::types::ListType;
+
+    #[test]
+    fn test_front_matter() {
+        let input = "---
+title: mytool
+section: 1
+date: \"2024-01-01\"
+other: ignored
+---
+# Usage
+";
+        let want = ManPage {
+            title: Some("mytool".to_string()),
+            section: Some("1".to_string()),
+            date: Some("2024-01-01".to_string()),
+            ..ManPage::default()
+        };
+        assert_eq!(front_matter(input), (want, "# Usage\n"));
+        assert_eq!(front_matter("# Usage\n"), (ManPage::default(), "# Usage\n"));
+        assert_eq!(front_matter("---\ntitle: x\n"), (ManPage::default(), "---\ntitle: x\n"));
+    }
+
+    #[test]
+    fn test_render() {
+        let input = "---
+title: mytool
+section: 1
+---
+# Synopsis
+
+`mytool` [*options*] **--verbose** *file*
+
+## Files
+
+.config and \\\\server\\share
+";
+        let (page, input) = front_matter(input);
+        let want = ".TH \"MYTOOL\" \"1\"
+.SH \"SYNOPSIS\"
+.PP
+\\fBmytool\\fR [\\fIoptions\\fR] \\fB\\-\\-verbose\\fR \\fIfile\\fR
+.SS \"Files\"
+.PP
+\\&.config and \\eserver\\eshare
+";
+        assert_eq!(render_with_metadata(&page, &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_title_from_heading() {
+        let input = "# mytool(1) -- do things
+
+## Options
+";
+        let want = ".TH \"MYTOOL\" \"1\"
+.SH NAME
+.PP
+mytool \\- do things
+.SS \"Options\"
+";
+        assert_eq!(render(&blocks(input)), want);
+    }
+
+    #[test]
+    fn test_nested_fonts() {
+        let input = "**bold *both* bold** plain\n";
+        let page = ManPage { title: Some("x".to_string()), ..ManPage::default() };
+        let want = ".TH \"X\"\n.PP\n\\fBbold \\f(BIboth\\fB bold\\fR plain\n";
+        assert_eq!(render_with_metadata(&page, &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_code_block() {
+        let page = ManPage { title: Some("x".to_string()), ..ManPage::default() };
+        let input = "```
+.start -x
+```
+";
+        let want = ".TH \"X\"
+.IP
+.nf
+\\f(CR
+\\&.start \\-x
+\\fR
+.fi
+";
+        assert_eq!(render_with_metadata(&page, &blocks(input)), want);
+    }
+
+    #[test]
+    fn test_lists() {
+        let paragraph = |text: &str| Paragraph(vec![Text(text.to_string())]);
+        let nested = List(vec![ListItem::OrderedListItem(vec![paragraph("one")], 1)], ListType::OrderedList, true);
+        let list = List(
+            vec![
+                ListItem::UnorderedListItem(vec![paragraph("first"), paragraph("more"), nested]),
+                ListItem::TaskListItem(vec![paragraph("done")], true),
+            ],
+            ListType::UnorderedList,
+            false,
+        );
+        let want = ".IP \\(bu 2
+first
+.IP
+more
+.RS
+.IP 1. 4
+one
+.RE
+.IP [x] 4
+done
+.PP
+";
+        assert_eq!(render(&[list]), want);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let options = Options { footnotes: true, ..Options::default() };
+        let input = "See[^a].
+
+[^a]: The note.
+";
+        let page = ManPage { title: Some("x".to_string()), ..ManPage::default() };
+        let want = ".TH \"X\"
+.PP
+See[1].
+.SH NOTES
+.IP [1] 4
+The note.
+";
+        assert_eq!(render_with_metadata(&page, &blocks_with_options(&options, input)), want);
+    }
+}
@@ -0,0 +1,126 @@
+//! The serialized form of a document, with the `serde` feature.
+//!
+//! A document is an object with the layout version and its blocks:
+//!
+//! ```json
+//! {"version": 1, "blocks": [{"type": "Paragraph", "content": [{"type": "Text", "content": "hi"}]}]}
+//! ```
+//!
+//! Every `Block`, `Inline`, `ListItem` and `ListType` is an object whose
+//! `type` is the name of its variant, except that `Block::LinkRefence` is
+//! written as `LinkReference`.  Variants with fields also have a `content`:
+//! the field itself when there is one, or an array of the fields in this
+//! order when there are several:
+//!
+//! * `Header`: inlines, level, attributes.
+//! * `CodeBlock`: info string or `null`, code, attributes.
+//! * `LinkReference`: label, url, title or `null`.
+//! * `List`: items, list type, whether the list is tight.
+//! * `FootnoteDefinition`: label, blocks.
+//! * `AbbreviationDefinition`: abbreviation, title.
+//! * `TaskListItem`: blocks, whether it is checked.
+//! * `OrderedListItem`: blocks, number.
+//! * `Code`: code, attributes.
+//! * `Link` and `Image`: inlines, url, title or `null`, attributes.
+//! * `RefLink`: inlines, url, title or an empty string.
+//! * `Emoji`: shortcode, emoji.
+//! * `WikiLink`: target, anchor or `null`, label or `null`.
+//! * `Reference`: pattern name, matched text, url.
+//! * `Abbreviation`: abbreviation, title.
+//!
+//! `Attributes` is an object with `id`, `classes` and `pairs`, the last as an
+//! array of `[key, value]` arrays.
+//!
+//! `VERSION` changes whenever this layout does, and documents of another
+//! version are rejected when deserializing.
+use crate::types::Block;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The version of the layout written by this crate.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Document {
+    #[serde(deserialize_with = "version")]
+    pub version: u32,
+    pub blocks: Vec<Block>,
+}
+
+impl Document {
+    /// A document of `blocks` in the current layout.
+    pub fn new(blocks: Vec<Block>) -> Self {
+        Document { version: VERSION, blocks }
+    }
+}
+
+fn version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version == VERSION {
+        Ok(version)
+    } else {
+        Err(serde::de::Error::custom(format!("unsupported document version {}, expected {}", version, VERSION)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::block::blocks_with_options;
+    use crate::options::Options;
+    use crate::spec::{examples, COMMONMARK, GFM};
+    use crate::types::Inline::Text;
+    use crate::types::{Block::Paragraph, ListType};
+
+    fn round_trip(document: &Document) -> Document {
+        let json = serde_json::to_string(document).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_layout() {
+        let document = Document::new(vec![Paragraph(vec![Text("hi".to_string())])]);
+        let want = r#"{"version":1,"blocks":[{"type":"Paragraph","content":[{"type":"Text","content":"hi"}]}]}"#;
+        assert_eq!(serde_json::to_string(&document).unwrap(), want);
+        assert_eq!(serde_json::to_string(&ListType::TaskList).unwrap(), r#"{"type":"TaskList"}"#);
+        let input = "# Hi {#top .title}\n";
+        let options = Options { attributes: true, ..Options::default() };
+        let want = r#"[{"type":"Header","content":[[{"type":"Text","content":"Hi"}],1,{"id":"top","classes":["title"],"pairs":[]}]}]"#;
+        assert_eq!(serde_json::to_string(&blocks_with_options(&options, input)).unwrap(), want);
+        let reference = Block::LinkRefence("a".to_string(), "/url".to_string(), None);
+        let want = r#"{"type":"LinkReference","content":["a","/url",null]}"#;
+        assert_eq!(serde_json::to_string(&reference).unwrap(), want);
+    }
+
+    #[test]
+    fn test_version() {
+        let json = r#"{"version":2,"blocks":[]}"#;
+        let error = serde_json::from_str::<Document>(json).unwrap_err();
+        assert!(error.to_string().starts_with("unsupported document version 2, expected 1"));
+        assert_eq!(serde_json::from_str::<Document>(r#"{"version":1,"blocks":[]}"#).unwrap(), Document::new(vec![]));
+    }
+
+    #[test]
+    fn test_round_trip_spec() {
+        let all = Options {
+            superscript: true,
+            emoji: true,
+            footnotes: true,
+            wiki_links: true,
+            highlight: true,
+            smart_punctuation: true,
+            attributes: true,
+            abbreviations: true,
+            math: true,
+            ..Options::default()
+        };
+        for spec in &[COMMONMARK, GFM] {
+            for example in examples(spec) {
+                for options in &[Options::default(), all.clone()] {
+                    let document = Document::new(blocks_with_options(options, &example.markdown));
+                    assert_eq!(round_trip(&document), document, "example {}", example.number);
+                }
+            }
+        }
+    }
+}
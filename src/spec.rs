@@ -0,0 +1,214 @@
+use crate::block::blocks_with_options;
+use crate::html;
+use crate::options::Options;
+
+/// The CommonMark spec, version 0.31.2, as vendored in `spec/commonmark.txt`.
+/// Tests named `test_commonmark_exN` and `test_gfm_exN` number the examples
+/// as version 0.29 of the specs does.
+#[cfg(test)]
+pub(crate) const COMMONMARK: &str = include_str!("../spec/commonmark.txt");
+/// The GitHub Flavored Markdown spec, version 0.28, as vendored in
+/// `spec/gfm.txt`.
+#[cfg(test)]
+pub(crate) const GFM: &str = include_str!("../spec/gfm.txt");
+
+const FENCE: &str = "````````````````````````````````";
+
+/// One example of a spec file: the Markdown input and the HTML it should
+/// render to.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Example {
+    /// The number of the example, counting from 1 in the order of the file.
+    pub number: usize,
+    /// The heading of the section the example appears in.
+    pub section: String,
+    /// The extension the example belongs to, e.g. `table` in the GFM spec.
+    pub extension: Option<String>,
+    pub markdown: String,
+    pub html: String,
+}
+
+/// Extract the examples of a spec file in the format of the CommonMark spec:
+/// an example is fenced by lines of 32 backticks, the opening one followed by
+/// `example`, and a line with a single `.` separates the Markdown from the
+/// expected HTML.  `→` stands for a tab.
+pub fn examples(spec: &str) -> Vec<Example> {
+    let mut examples = vec![];
+    let mut section = String::new();
+    let mut lines = spec.lines();
+    while let Some(line) = lines.next() {
+        if line.starts_with('#') {
+            if let Some(heading) = line.trim_start_matches('#').strip_prefix(' ') {
+                section = heading.trim().to_string();
+            }
+            continue;
+        }
+        let extension = match line.strip_prefix(FENCE).and_then(|rest| rest.trim().strip_prefix("example")) {
+            Some(extension) => Some(extension.trim()).filter(|extension| !extension.is_empty()),
+            None => continue,
+        };
+        let mut markdown = String::new();
+        let mut html = String::new();
+        let mut in_html = false;
+        for line in lines.by_ref() {
+            if line == FENCE {
+                break;
+            }
+            if line == "." && !in_html {
+                in_html = true;
+                continue;
+            }
+            let part = if in_html { &mut html } else { &mut markdown };
+            part.push_str(&line.replace('→', "\t"));
+            part.push('\n');
+        }
+        examples.push(Example {
+            number: examples.len() + 1,
+            section: section.clone(),
+            extension: extension.map(str::to_string),
+            markdown,
+            html,
+        });
+    }
+    examples
+}
+
+impl Example {
+    /// Parse and render the example with `options`.
+    pub fn render(&self, options: &Options) -> String {
+        html::render(&blocks_with_options(options, &self.markdown))
+    }
+
+    pub fn passes(&self, options: &Options) -> bool {
+        self.render(options) == self.html
+    }
+}
+
+/// The examples of one section that pass and fail.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SectionReport {
+    pub section: String,
+    pub passed: Vec<usize>,
+    pub failed: Vec<usize>,
+}
+
+/// Run every example of `spec` and group the results by section, in the
+/// order the sections first appear.
+pub fn run(spec: &str, options: &Options) -> Vec<SectionReport> {
+    let mut reports: Vec<SectionReport> = vec![];
+    for example in examples(spec) {
+        let index = match reports.iter().position(|report| report.section == example.section) {
+            Some(index) => index,
+            None => {
+                reports.push(SectionReport { section: example.section.clone(), passed: vec![], failed: vec![] });
+                reports.len() - 1
+            }
+        };
+        if example.passes(options) {
+            reports[index].passed.push(example.number);
+        } else {
+            reports[index].failed.push(example.number);
+        }
+    }
+    reports
+}
+
+/// A table with one line per section and a total, e.g. for printing the
+/// result of `run`.
+pub fn summary(reports: &[SectionReport]) -> String {
+    let width = reports.iter().map(|report| report.section.chars().count()).max().unwrap_or(0).max(5);
+    let mut summary = String::new();
+    let (mut passed, mut total) = (0, 0);
+    for report in reports {
+        let count = report.passed.len() + report.failed.len();
+        let score = format!("{}/{}", report.passed.len(), count);
+        summary.push_str(&format!("{:<width$}  {:>7}", report.section, score, width = width));
+        if !report.failed.is_empty() {
+            let failed: Vec<String> = report.failed.iter().map(usize::to_string).collect();
+            summary.push_str(&format!("  failed: {}", failed.join(", ")));
+        }
+        summary.push('\n');
+        passed += report.passed.len();
+        total += count;
+    }
+    let score = format!("{}/{}", passed, total);
+    summary.push_str(&format!("{:<width$}  {:>7}\n", "Total", score, width = width));
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn passed(reports: &[SectionReport]) -> usize {
+        reports.iter().map(|report| report.passed.len()).sum()
+    }
+
+    #[test]
+    fn test_examples() {
+        let spec = "# Tabs
+
+```````````````````````````````` example
+→foo
+.
+<pre><code>foo
+</code></pre>
+````````````````````````````````
+
+## Tables (extension)
+
+```````````````````````````````` example table
+| a |
+.
+<table>
+````````````````````````````````
+";
+        let want = vec![
+            Example {
+                number: 1,
+                section: "Tabs".to_string(),
+                extension: None,
+                markdown: "\tfoo\n".to_string(),
+                html: "<pre><code>foo\n</code></pre>\n".to_string(),
+            },
+            Example {
+                number: 2,
+                section: "Tables (extension)".to_string(),
+                extension: Some("table".to_string()),
+                markdown: "| a |\n".to_string(),
+                html: "<table>\n".to_string(),
+            },
+        ];
+        assert_eq!(examples(spec), want);
+    }
+
+    #[test]
+    fn test_vendored_specs() {
+        assert_eq!(examples(COMMONMARK).len(), 652);
+        assert_eq!(examples(GFM).len(), 657);
+        assert!(examples(GFM).iter().all(|example| !example.section.is_empty()));
+    }
+
+    #[test]
+    fn test_summary() {
+        let reports = vec![
+            SectionReport { section: "Tabs".to_string(), passed: vec![1, 2], failed: vec![3] },
+            SectionReport { section: "Entity references".to_string(), passed: vec![4], failed: vec![] },
+        ];
+        let want = "Tabs                   2/3  failed: 3
+Entity references      1/1
+Total                  3/4
+";
+        assert_eq!(summary(&reports), want);
+    }
+
+    /// Conformance must not regress; raise the bounds when more examples
+    /// pass.
+    #[test]
+    fn test_conformance() {
+        let options = Options::default();
+        assert!(passed(&run(COMMONMARK, &options)) >= 378);
+        assert!(passed(&run(GFM, &options)) >= 380);
+    }
+}
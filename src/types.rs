@@ -1,16 +1,28 @@
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "content"))]
 pub enum Block {
-    Header(Vec<Inline>, usize),
+    Header(Vec<Inline>, usize, Attributes),
     Paragraph(Vec<Inline>),
     Blockquote(Vec<Block>),
-    CodeBlock(Option<String>, String),
+    // info string, content, and attributes
+    CodeBlock(Option<String>, String, Attributes),
+    // label, url, and title
+    #[cfg_attr(feature = "serde", serde(rename = "LinkReference"))]
     LinkRefence(String, String, Option<String>),
-    List(Vec<ListItem>, ListType),
+    // items, type, and whether the list is tight
+    List(Vec<ListItem>, ListType, bool),
     Formula(String),
     HorizontalRule,
+    // [^label]: content
+    FootnoteDefinition(String, Vec<Block>),
+    // *[abbreviation]: title
+    AbbreviationDefinition(String, String),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "content"))]
 pub enum ListItem {
     TaskListItem(Vec<Block>, bool),
     OrderedListItem(Vec<Block>, usize),
@@ -18,6 +30,8 @@ pub enum ListItem {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "content"))]
 pub enum ListType {
     TaskList,
     OrderedList,
@@ -25,16 +39,19 @@ pub enum ListType {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "content"))]
 pub enum Inline {
-    Break,
+    HardBreak,
+    SoftBreak,
     Text(String),
-    Code(String),
+    Code(String, Attributes),
     Literal(char),
-    Link(Vec<Inline>, String, Option<String>),
+    Link(Vec<Inline>, String, Option<String>, Attributes),
     // [text](url "title")
     RefLink(Vec<Inline>, String, String),
     // [alt](url "title")
-    Image(String, String, Option<String>),
+    Image(Vec<Inline>, String, Option<String>, Attributes),
     Emphasis(Vec<Inline>),
     Strong(Vec<Inline>),
     Formula(String),
@@ -43,4 +60,25 @@ pub enum Inline {
     Subscript(Vec<Inline>),
     Superscript(Vec<Inline>),
     RawHTML(String),
+    // :shortcode: and the emoji it stands for
+    Emoji(String, String),
+    // [^label]
+    FootnoteReference(String),
+    // [[target#anchor|label]]
+    WikiLink(String, Option<String>, Option<String>),
+    // pattern name, matched text, url
+    Reference(String, String, String),
+    // ==highlighted==
+    Highlight(Vec<Inline>),
+    // abbreviation, title
+    Abbreviation(String, String),
+}
+
+/// An attribute list such as `{#id .class key=value}`.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attributes {
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub pairs: Vec<(String, String)>,
 }